@@ -0,0 +1,59 @@
+//! Times dense operator products. Run with `cargo bench --bench kernels`,
+//! optionally adding `--features rayon` to compare the parallel kernels,
+//! which fall back to the serial ones for small products.
+
+use std::time::{Duration, Instant};
+
+use qompute::prelude::*;
+
+fn operator(n: usize, seed: usize) -> Operator<f64> {
+    let mut op = Operator::new_with_shape((n, n).into());
+    for i in 0..n {
+        for j in 0..n {
+            let x = (i * 31 + j * 17 + seed) % 23;
+            op[(i, j)] = Complex::new(x as f64 / 23. - 0.5, (x % 7) as f64 / 7.);
+        }
+    }
+    op
+}
+
+/// Runs `f` until a second has passed and returns the mean time per call,
+/// along with a checksum that keeps the work from being optimized away.
+fn time(mut f: impl FnMut() -> f64) -> (Duration, f64) {
+    let start = Instant::now();
+    let (mut runs, mut checksum) = (0u32, 0.);
+
+    while runs == 0 || start.elapsed() < Duration::from_secs(1) {
+        checksum += f();
+        runs += 1;
+    }
+
+    (start.elapsed() / runs, checksum)
+}
+
+fn main() {
+    for n in [8, 32, 64, 128, 256, 512] {
+        let (a, b) = (operator(n, 0), operator(n, 5));
+        let (mean, checksum) = time(|| (&a * &b)[(0, 0)].re);
+        println!("matmul     {n:>4}x{n:<4} {mean:>12.2?}  (checksum {checksum:.3})");
+
+        #[cfg(feature = "rayon")]
+        {
+            let (mean, checksum) = time(|| a.par_checked_mul(&b).unwrap()[(0, 0)].re);
+            println!("par_matmul {n:>4}x{n:<4} {mean:>12.2?}  (checksum {checksum:.3})");
+        }
+    }
+
+    for n in [64, 256, 1024, 4096] {
+        let a = operator(n, 0);
+        let ket = (0..n).map(|i| Complex::new(i as f64, 0.)).to_ket();
+        let (mean, checksum) = time(|| (&a * &ket)[0].re);
+        println!("matvec     {n:>4}x{n:<4} {mean:>12.2?}  (checksum {checksum:.3})");
+
+        #[cfg(feature = "rayon")]
+        {
+            let (mean, checksum) = time(|| a.par_checked_apply(&ket).unwrap()[0].re);
+            println!("par_matvec {n:>4}x{n:<4} {mean:>12.2?}  (checksum {checksum:.3})");
+        }
+    }
+}
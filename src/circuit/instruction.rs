@@ -0,0 +1,204 @@
+use crate::prelude::*;
+
+use smallvec::SmallVec;
+
+use crate::complex::gates::GateMatrix;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GateKind<T: Float> {
+    I,
+    H,
+    X,
+    Y,
+    Z,
+    S,
+    Sdg,
+    T,
+    Tdg,
+    /// `diag(1, e^{i theta})`, taking `theta` as its only parameter.
+    Phase,
+    /// Rotations `exp(-i theta P / 2)` about the Pauli axis `P`.
+    Rx,
+    Ry,
+    Rz,
+    /// The general single-qubit gate `U(theta, phi, lambda)`, equal to
+    /// `[[cos(theta/2), -e^{i lambda} sin(theta/2)],
+    ///   [e^{i phi} sin(theta/2), e^{i (phi + lambda)} cos(theta/2)]]`.
+    U,
+    Swap,
+    /// An arbitrary `2^k x 2^k` unitary acting on `k` targets.
+    Unitary(Operator<T>),
+    Measure,
+    Barrier,
+}
+
+impl<T: Float> GateKind<T> {
+    pub fn is_unitary(&self) -> bool {
+        !matches!(self, GateKind::Measure | GateKind::Barrier)
+    }
+
+    pub fn n_params(&self) -> usize {
+        match self {
+            GateKind::Phase | GateKind::Rx | GateKind::Ry | GateKind::Rz => 1,
+            GateKind::U => 3,
+            _ => 0,
+        }
+    }
+
+    /// The number of target qubits, or `None` if the gate takes any number.
+    pub fn n_targets(&self) -> Option<usize> {
+        match self {
+            GateKind::Swap => Some(2),
+            GateKind::Unitary(op) => Some(op.rows().trailing_zeros() as usize),
+            GateKind::Barrier => None,
+            _ => Some(1),
+        }
+    }
+}
+
+/// Guards an instruction on the value of some classical bits, read with
+/// `clbits[0]` as the least significant bit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Condition {
+    pub clbits: SmallVec<[usize; 4]>,
+    pub value: u64,
+}
+
+impl Condition {
+    pub fn holds(&self, register: &[bool]) -> bool {
+        let value = self
+            .clbits
+            .iter()
+            .rev()
+            .fold(0, |acc, &c| (acc << 1) | register[c] as u64);
+
+        value == self.value
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instruction<T: Float> {
+    pub kind: GateKind<T>,
+    pub targets: SmallVec<[usize; 2]>,
+    pub controls: SmallVec<[usize; 2]>,
+    pub params: SmallVec<[T; 3]>,
+    pub clbits: SmallVec<[usize; 1]>,
+    pub condition: Option<Condition>,
+}
+
+impl<T: Float> Instruction<T> {
+    pub fn new(kind: GateKind<T>, targets: &[usize]) -> Self {
+        Self {
+            kind,
+            targets: targets.into(),
+            controls: SmallVec::new(),
+            params: SmallVec::new(),
+            clbits: SmallVec::new(),
+            condition: None,
+        }
+    }
+
+    pub fn with_controls(mut self, controls: &[usize]) -> Self {
+        self.controls = controls.into();
+        self
+    }
+
+    pub fn with_params(mut self, params: &[T]) -> Self {
+        self.params = params.into();
+        self
+    }
+
+    pub fn with_clbits(mut self, clbits: &[usize]) -> Self {
+        self.clbits = clbits.into();
+        self
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// All qubits touched by the instruction, controls first.
+    pub fn qubits(&self) -> impl Iterator<Item = usize> + '_ {
+        self.controls.iter().chain(self.targets.iter()).copied()
+    }
+
+    /// The matrix acting on `targets`, ignoring any controls. Returns `None`
+    /// for non-unitary instructions.
+    pub fn matrix(&self) -> Option<Operator<T>> {
+        Some(match &self.kind {
+            GateKind::I => gates::i(),
+            GateKind::H => gates::h(),
+            GateKind::X => gates::x(),
+            GateKind::Y => gates::y(),
+            GateKind::Z => gates::z(),
+            GateKind::S => gates::s(),
+            GateKind::Sdg => gates::sdg(),
+            GateKind::T => gates::t(),
+            GateKind::Tdg => gates::tdg(),
+            GateKind::Swap => gates::swap(),
+            GateKind::Phase => gates::phase(self.params[0]),
+            GateKind::Rx => gates::rx(self.params[0]),
+            GateKind::Ry => gates::ry(self.params[0]),
+            GateKind::Rz => gates::rz(self.params[0]),
+            GateKind::U => gates::u(self.params[0], self.params[1], self.params[2]),
+            GateKind::Unitary(op) => op.clone(),
+            GateKind::Measure | GateKind::Barrier => return None,
+        })
+    }
+
+    /// [`Instruction::matrix`] in the cheapest form that holds it exactly.
+    ///
+    /// Built-in gates map straight onto their diagonal, permutation or dense
+    /// form. Only [`GateKind::Unitary`] matrices are scanned to find theirs.
+    pub fn gate_matrix(&self) -> Option<GateMatrix<T>> {
+        use GateMatrix::{Dense, Diagonal, Permutation};
+
+        Some(match &self.kind {
+            GateKind::I => Diagonal(gates::i_diag()),
+            GateKind::X => Permutation(gates::x_perm()),
+            GateKind::Swap => Permutation(gates::swap_perm()),
+            GateKind::Z => Diagonal(gates::z_diag()),
+            GateKind::S => Diagonal(gates::s_diag()),
+            GateKind::Sdg => Diagonal(gates::sdg_diag()),
+            GateKind::T => Diagonal(gates::t_diag()),
+            GateKind::Tdg => Diagonal(gates::tdg_diag()),
+            GateKind::Phase => Diagonal(gates::phase_diag(self.params[0])),
+            GateKind::Rz => Diagonal(gates::rz_diag(self.params[0])),
+            GateKind::Unitary(op) => GateMatrix::from(op.clone()),
+            GateKind::Measure | GateKind::Barrier => return None,
+            _ => Dense(self.matrix()?),
+        })
+    }
+
+    /// The matrix acting on `controls` followed by `targets`.
+    pub fn controlled_matrix(&self) -> Option<Operator<T>> {
+        Some(gates::controlled(&self.matrix()?, self.controls.len()))
+    }
+
+    /// The instruction undoing this one, or `None` if it is not unitary or
+    /// is classically conditioned.
+    pub fn inverse(&self) -> Option<Self> {
+        if self.condition.is_some() {
+            return None;
+        }
+
+        let mut inv = self.clone();
+
+        match &self.kind {
+            GateKind::Measure | GateKind::Barrier => return None,
+            GateKind::S => inv.kind = GateKind::Sdg,
+            GateKind::Sdg => inv.kind = GateKind::S,
+            GateKind::T => inv.kind = GateKind::Tdg,
+            GateKind::Tdg => inv.kind = GateKind::T,
+            GateKind::Phase | GateKind::Rx | GateKind::Ry | GateKind::Rz => {
+                inv.params[0] = -self.params[0]
+            }
+            GateKind::U => inv.params = [-self.params[0], -self.params[2], -self.params[1]].into(),
+            GateKind::Unitary(op) => inv.kind = GateKind::Unitary(op.dagger()),
+            _ => {}
+        }
+
+        Some(inv)
+    }
+}
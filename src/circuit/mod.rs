@@ -0,0 +1,571 @@
+pub mod instruction;
+pub mod qasm;
+
+pub use instruction::{Condition, GateKind, Instruction};
+pub use qasm::{QasmError, QasmVersion};
+
+use crate::prelude::*;
+
+use rand::Rng;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq, Hash)]
+pub enum CircuitError {
+    #[error("Instruction {0} is not unitary and cannot be simulated as such")]
+    NonUnitary(usize),
+    #[error("The circuit acts on {expected} qubits, but the state has {found}")]
+    NonMatchingQubits { expected: usize, found: usize },
+    #[error("A circuit can only act on a power-of-two sized state")]
+    NonQubitState,
+    #[error("Instruction {0} cannot be expressed in the requested format")]
+    Unexportable(usize),
+    #[error("A unitary gate needs a square matrix of size 2^k with k > 0, but found {0}")]
+    NonQubitGate(Shape),
+    #[error("The gate takes {expected} targets, but was given {found}")]
+    WrongTargets { expected: usize, found: usize },
+    #[error("The gate takes {expected} parameters, but was given {found}")]
+    WrongParams { expected: usize, found: usize },
+    #[error("A measurement needs one clbit per target, but was given {found} for {expected}")]
+    WrongClbits { expected: usize, found: usize },
+    #[error("Qubit {0} is out of range")]
+    QubitOutOfRange(usize),
+    #[error("Qubit {0} is used more than once")]
+    RepeatedQubit(usize),
+    #[error("Clbit {0} is out of range")]
+    ClbitOutOfRange(usize),
+}
+
+/// An ordered list of instructions over `n_qubits` qubits and `n_clbits`
+/// classical bits.
+///
+/// Builder methods panic if they are given an out-of-range or repeated qubit
+/// or classical bit; [`Circuit::try_push`] and [`Circuit::try_gate`] report
+/// the same problems as a [`CircuitError`] instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Circuit<T: Float> {
+    n_qubits: usize,
+    n_clbits: usize,
+    instructions: Vec<Instruction<T>>,
+}
+
+impl<T: Float> Circuit<T> {
+    pub fn new(n_qubits: usize, n_clbits: usize) -> Self {
+        Self {
+            n_qubits,
+            n_clbits,
+            instructions: Vec::new(),
+        }
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    pub fn n_clbits(&self) -> usize {
+        self.n_clbits
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    pub fn instructions(&self) -> &[Instruction<T>] {
+        &self.instructions
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Instruction<T>> {
+        self.instructions.iter()
+    }
+
+    pub fn push(&mut self, instruction: Instruction<T>) -> &mut Self {
+        self.try_push(instruction)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Appends `instruction`, or returns why it does not fit in the circuit.
+    pub fn try_push(&mut self, instruction: Instruction<T>) -> Result<&mut Self, CircuitError> {
+        self.check(&instruction)?;
+        self.instructions.push(instruction);
+        Ok(self)
+    }
+
+    fn check(&self, instruction: &Instruction<T>) -> Result<(), CircuitError> {
+        let Instruction {
+            kind,
+            targets,
+            params,
+            clbits,
+            condition,
+            ..
+        } = instruction;
+
+        if let GateKind::Unitary(op) = kind {
+            let shape = op.shape();
+            if shape.rows != shape.cols || shape.rows < 2 || !shape.rows.is_power_of_two() {
+                return Err(CircuitError::NonQubitGate(shape));
+            }
+        }
+
+        if let Some(expected) = kind.n_targets() {
+            if targets.len() != expected {
+                return Err(CircuitError::WrongTargets {
+                    expected,
+                    found: targets.len(),
+                });
+            }
+        }
+        if params.len() != kind.n_params() {
+            return Err(CircuitError::WrongParams {
+                expected: kind.n_params(),
+                found: params.len(),
+            });
+        }
+
+        let mut qubits = instruction.qubits().collect::<Vec<_>>();
+        if let Some(&q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            return Err(CircuitError::QubitOutOfRange(q));
+        }
+        qubits.sort_unstable();
+        if let Some(w) = qubits.windows(2).find(|w| w[0] == w[1]) {
+            return Err(CircuitError::RepeatedQubit(w[0]));
+        }
+
+        let conditioned = condition.iter().flat_map(|c| c.clbits.iter());
+        if let Some(&c) = clbits
+            .iter()
+            .chain(conditioned)
+            .find(|&&c| c >= self.n_clbits)
+        {
+            return Err(CircuitError::ClbitOutOfRange(c));
+        }
+        if *kind == GateKind::Measure && clbits.len() != targets.len() {
+            return Err(CircuitError::WrongClbits {
+                expected: targets.len(),
+                found: clbits.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Appends every instruction of `other`, which must fit in this circuit.
+    pub fn append(&mut self, other: &Circuit<T>) -> &mut Self {
+        other.iter().cloned().for_each(|i| {
+            self.push(i);
+        });
+        self
+    }
+
+    pub fn i(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::I, &[qubit]))
+    }
+
+    pub fn h(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::H, &[qubit]))
+    }
+
+    pub fn x(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::X, &[qubit]))
+    }
+
+    pub fn y(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Y, &[qubit]))
+    }
+
+    pub fn z(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Z, &[qubit]))
+    }
+
+    pub fn s(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::S, &[qubit]))
+    }
+
+    pub fn sdg(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Sdg, &[qubit]))
+    }
+
+    pub fn t(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::T, &[qubit]))
+    }
+
+    pub fn tdg(&mut self, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Tdg, &[qubit]))
+    }
+
+    pub fn rx(&mut self, theta: T, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Rx, &[qubit]).with_params(&[theta]))
+    }
+
+    pub fn ry(&mut self, theta: T, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Ry, &[qubit]).with_params(&[theta]))
+    }
+
+    pub fn rz(&mut self, theta: T, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Rz, &[qubit]).with_params(&[theta]))
+    }
+
+    pub fn u(&mut self, theta: T, phi: T, lambda: T, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::U, &[qubit]).with_params(&[theta, phi, lambda]))
+    }
+
+    pub fn phase(&mut self, theta: T, qubit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Phase, &[qubit]).with_params(&[theta]))
+    }
+
+    pub fn cnot(&mut self, control: usize, target: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::X, &[target]).with_controls(&[control]))
+    }
+
+    pub fn cz(&mut self, control: usize, target: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Z, &[target]).with_controls(&[control]))
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Swap, &[a, b]))
+    }
+
+    pub fn gate(&mut self, op: Operator<T>, targets: &[usize]) -> &mut Self {
+        self.push(Instruction::new(GateKind::Unitary(op), targets))
+    }
+
+    /// [`Circuit::gate`], returning an error for a matrix that is not a
+    /// qubit gate or does not match `targets`.
+    pub fn try_gate(
+        &mut self,
+        op: Operator<T>,
+        targets: &[usize],
+    ) -> Result<&mut Self, CircuitError> {
+        self.try_push(Instruction::new(GateKind::Unitary(op), targets))
+    }
+
+    pub fn measure(&mut self, qubit: usize, clbit: usize) -> &mut Self {
+        self.push(Instruction::new(GateKind::Measure, &[qubit]).with_clbits(&[clbit]))
+    }
+
+    pub fn barrier(&mut self, qubits: &[usize]) -> &mut Self {
+        self.push(Instruction::new(GateKind::Barrier, qubits))
+    }
+
+    /// The circuit undoing this one. Barriers are kept in place, and `None`
+    /// is returned if any instruction is a measurement or is conditioned.
+    pub fn inverse(&self) -> Option<Self> {
+        let instructions = self
+            .iter()
+            .rev()
+            .map(|i| match i.kind {
+                GateKind::Barrier => Some(i.clone()),
+                _ => i.inverse(),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            n_qubits: self.n_qubits,
+            n_clbits: self.n_clbits,
+            instructions,
+        })
+    }
+
+    /// Applies every instruction in order to `state`, skipping barriers.
+    /// Fails on measurements and classically conditioned instructions.
+    pub fn run(&self, state: &mut StateVector<T>) -> Result<(), CircuitError> {
+        if state.n_qubits() != self.n_qubits {
+            return Err(CircuitError::NonMatchingQubits {
+                expected: self.n_qubits,
+                found: state.n_qubits(),
+            });
+        }
+
+        for (idx, instruction) in self.iter().enumerate() {
+            if instruction.kind == GateKind::Barrier {
+                continue;
+            }
+
+            if instruction.condition.is_some() {
+                return Err(CircuitError::NonUnitary(idx));
+            }
+
+            let gate = instruction
+                .gate_matrix()
+                .ok_or(CircuitError::NonUnitary(idx))?;
+            state.apply_gate(&gate, &instruction.controls, &instruction.targets);
+        }
+
+        Ok(())
+    }
+
+    /// Applies every instruction in order to `state`, sampling measurements
+    /// with `rng`, and returns the final classical register.
+    pub fn execute<R: Rng + ?Sized>(
+        &self,
+        state: &mut StateVector<T>,
+        rng: &mut R,
+    ) -> Result<Vec<bool>, CircuitError> {
+        if state.n_qubits() != self.n_qubits {
+            return Err(CircuitError::NonMatchingQubits {
+                expected: self.n_qubits,
+                found: state.n_qubits(),
+            });
+        }
+
+        let mut clbits = vec![false; self.n_clbits];
+
+        for instruction in self {
+            if let Some(cond) = &instruction.condition {
+                if !cond.holds(&clbits) {
+                    continue;
+                }
+            }
+
+            match instruction.kind {
+                GateKind::Barrier => {}
+                GateKind::Measure => {
+                    let outcome = state.measure(&instruction.targets, rng);
+                    instruction
+                        .clbits
+                        .iter()
+                        .zip(outcome)
+                        .for_each(|(&c, b)| clbits[c] = b);
+                }
+                _ => {
+                    let gate = instruction.gate_matrix().unwrap();
+                    state.apply_gate(&gate, &instruction.controls, &instruction.targets);
+                }
+            }
+        }
+
+        Ok(clbits)
+    }
+
+    pub fn run_ket(&self, ket: Ket<T>) -> Result<Ket<T>, CircuitError> {
+        let mut state = StateVector::try_from(ket).map_err(|_| CircuitError::NonQubitState)?;
+        self.run(&mut state)?;
+        Ok(state.into_ket())
+    }
+
+    /// The full `2^n x 2^n` unitary of the circuit, built column by column.
+    pub fn unitary(&self) -> Result<Operator<T>, CircuitError> {
+        let dim = 1 << self.n_qubits;
+        let mut op = Operator::new_with_shape((dim, dim).into());
+
+        for col in 0..dim {
+            let ket = (0..dim)
+                .map(|i| Complex::new(if i == col { T::one() } else { T::zero() }, T::zero()))
+                .to_ket();
+            let out = self.run_ket(ket)?;
+            (0..dim).for_each(|row| op[(row, col)] = out[row]);
+        }
+
+        Ok(op)
+    }
+}
+
+impl<'a, T: Float> IntoIterator for &'a Circuit<T> {
+    type Item = &'a Instruction<T>;
+    type IntoIter = std::slice::Iter<'a, Instruction<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Float> IntoIterator for Circuit<T> {
+    type Item = Instruction<T>;
+    type IntoIter = std::vec::IntoIter<Instruction<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.instructions.into_iter()
+    }
+}
+
+impl<T: Float> Extend<Instruction<T>> for Circuit<T> {
+    fn extend<I: IntoIterator<Item = Instruction<T>>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|i| {
+            self.push(i);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn assert_close(lhs: &Operator<f64>, rhs: &Operator<f64>) {
+        assert_eq!(lhs.shape(), rhs.shape());
+        assert!(lhs
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+    }
+
+    #[test]
+    fn test_bell_circuit() {
+        let mut circ = Circuit::<f64>::new(2, 2);
+        circ.h(0).cnot(0, 1).barrier(&[0, 1]);
+
+        assert_eq!(circ.len(), 3);
+        assert_eq!(
+            circ.iter().map(|i| i.kind.clone()).collect::<Vec<_>>(),
+            [GateKind::H, GateKind::X, GateKind::Barrier]
+        );
+
+        let mut state = StateVector::new(2);
+        circ.run(&mut state).unwrap();
+
+        let amp = 0.5f64.sqrt();
+        assert!(state
+            .amplitudes()
+            .iter()
+            .zip([amp, 0., 0., amp])
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+
+        circ.measure(0, 0);
+        assert_eq!(
+            circ.run(&mut StateVector::new(2)),
+            Err(CircuitError::NonUnitary(3))
+        );
+        assert!(circ.inverse().is_none());
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut circ = Circuit::<f64>::new(2, 2);
+        circ.h(0).cnot(0, 1).measure(0, 1).measure(1, 0);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..10 {
+            let clbits = circ.execute(&mut StateVector::new(2), &mut rng).unwrap();
+            assert_eq!(clbits[0], clbits[1]);
+        }
+
+        let mut circ = Circuit::<f64>::new(1, 1);
+        circ.x(0).measure(0, 0);
+        let mut state = StateVector::new(1);
+        assert_eq!(circ.execute(&mut state, &mut rng), Ok(vec![true]));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let op = Operator::from([[cmpx!(0.6), cmpx!(0.8 j)], [cmpx!(0.8 j), cmpx!(0.6)]]);
+
+        let mut circ = Circuit::<f64>::new(3, 0);
+        circ.h(0)
+            .phase(0.3, 1)
+            .cz(1, 2)
+            .swap(0, 2)
+            .gate(op, &[1])
+            .y(2);
+
+        let mut full = circ.clone();
+        full.append(&circ.inverse().unwrap());
+        assert_eq!(full.len(), 2 * circ.len());
+
+        let identity = Operator::new_with_shape((8, 8).into());
+        assert_close(&full.unitary().unwrap(), &identity);
+
+        let cnot = Operator::from([
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 0., 1.],
+            [0., 0., 1., 0.],
+        ]);
+        let mut circ = Circuit::<f64>::new(2, 0);
+        circ.cnot(0, 1);
+        assert_close(&circ.unitary().unwrap(), &cnot);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        Circuit::<f32>::new(2, 0).cnot(0, 2);
+    }
+
+    #[test]
+    fn test_gate_matrix() {
+        use crate::complex::gates::GateMatrix;
+
+        // Special angles can make a dense gate diagonal, so the forms are only
+        // compared for generic ones.
+        fn check<T: Float + std::fmt::Debug>(params: [T; 3], generic: bool) {
+            let kinds = [
+                GateKind::I,
+                GateKind::H,
+                GateKind::X,
+                GateKind::Y,
+                GateKind::Z,
+                GateKind::S,
+                GateKind::Sdg,
+                GateKind::T,
+                GateKind::Tdg,
+                GateKind::Phase,
+                GateKind::Rx,
+                GateKind::Ry,
+                GateKind::Rz,
+                GateKind::U,
+                GateKind::Swap,
+                GateKind::Unitary(gates::cz()),
+            ];
+
+            for kind in kinds {
+                let n = kind.n_targets().unwrap();
+                let instruction = Instruction::<T>::new(kind.clone(), &[0, 1][..n])
+                    .with_params(&params[..kind.n_params()]);
+
+                let gate = instruction.gate_matrix().unwrap();
+                assert_eq!(gate.to_operator(), instruction.matrix().unwrap());
+                if !generic {
+                    continue;
+                }
+                assert_eq!(
+                    std::mem::discriminant(&gate),
+                    std::mem::discriminant(&GateMatrix::from(instruction.matrix().unwrap()))
+                );
+            }
+        }
+
+        for (params, generic) in [
+            ([0.7, -0.2, 1.3], true),
+            ([-3.1, 2.4, 100.], true),
+            ([0., 0., 0.], false),
+        ] {
+            check::<f64>(params, generic);
+            check::<f32>(params.map(|p| p as f32), generic);
+        }
+    }
+
+    #[test]
+    fn test_try_push() {
+        let mut circ = Circuit::<f64>::new(2, 1);
+        let three = Operator::new_with_shape((3, 3).into());
+
+        assert_eq!(
+            circ.try_gate(three.clone(), &[0]).unwrap_err().to_string(),
+            "A unitary gate needs a square matrix of size 2^k with k > 0, but found 3x3"
+        );
+        assert_eq!(
+            circ.try_gate(Operator::new_with_shape((4, 4).into()), &[0]),
+            Err(CircuitError::WrongTargets {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            circ.try_push(Instruction::new(GateKind::X, &[1]).with_controls(&[1])),
+            Err(CircuitError::RepeatedQubit(1))
+        );
+        assert_eq!(
+            circ.try_push(Instruction::new(GateKind::Measure, &[0]).with_clbits(&[1])),
+            Err(CircuitError::ClbitOutOfRange(1))
+        );
+        assert!(circ.is_empty());
+
+        assert!(circ.try_gate(gates::swap(), &[1, 0]).is_ok());
+        assert_eq!(circ.len(), 1);
+    }
+}
@@ -0,0 +1,475 @@
+use crate::prelude::*;
+
+use std::fmt::Write;
+
+use num::Zero;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QasmVersion {
+    V2,
+    V3,
+}
+
+/// The standard name of a gate with `n_controls` controls, if the version's
+/// standard library has one.
+fn gate_name<T: Float>(
+    kind: &GateKind<T>,
+    n_controls: usize,
+    version: QasmVersion,
+) -> Option<&'static str> {
+    use GateKind::*;
+    use QasmVersion::*;
+
+    Some(match (kind, n_controls, version) {
+        (I, 0, _) => "id",
+        (H, 0, _) => "h",
+        (H, 1, _) => "ch",
+        (X, 0, _) => "x",
+        (X, 1, _) => "cx",
+        (X, 2, _) => "ccx",
+        (Y, 0, _) => "y",
+        (Y, 1, _) => "cy",
+        (Z, 0, _) => "z",
+        (Z, 1, _) => "cz",
+        (S, 0, _) => "s",
+        (Sdg, 0, _) => "sdg",
+        (T, 0, _) => "t",
+        (Tdg, 0, _) => "tdg",
+        (Phase, 0, V2) => "u1",
+        (Phase, 1, V2) => "cu1",
+        (Phase, 0, V3) => "p",
+        (Phase, 1, V3) => "cp",
+        (Rx, 0, _) => "rx",
+        (Rx, 1, _) => "crx",
+        (Ry, 0, _) => "ry",
+        (Ry, 1, _) => "cry",
+        (Rz, 0, _) => "rz",
+        (Rz, 1, _) => "crz",
+        (U, 0, V2) => "u3",
+        (U, 1, V2) => "cu3",
+        (U, 0, V3) => "U",
+        (U, 1, V3) => "cu",
+        (Swap, 0, _) => "swap",
+        (Swap, 1, _) => "cswap",
+        _ => return None,
+    })
+}
+
+/// Rewrites a [`GateKind::Unitary`] whose matrix, including any leading
+/// qubits that act as controls, is exactly one of the fixed standard gates.
+fn standard_form<T: Float>(instruction: &Instruction<T>) -> Option<Instruction<T>> {
+    use GateKind::*;
+
+    let op = match &instruction.kind {
+        Unitary(op) => op,
+        _ => return None,
+    };
+
+    let candidates = [
+        (I, 0),
+        (H, 0),
+        (X, 0),
+        (Y, 0),
+        (Z, 0),
+        (S, 0),
+        (Sdg, 0),
+        (T, 0),
+        (Tdg, 0),
+        (Swap, 0),
+        (H, 1),
+        (X, 1),
+        (Y, 1),
+        (Z, 1),
+        (Swap, 1),
+        (X, 2),
+    ];
+
+    let (kind, n_controls) = candidates.into_iter().find(|(kind, n_controls)| {
+        let base = Instruction::new(kind.clone(), &[]).matrix().unwrap();
+        base.rows() << n_controls == op.rows() && gates::controlled(&base, *n_controls) == *op
+    })?;
+
+    let (controls, targets) = instruction.targets.split_at(n_controls);
+    let mut named = instruction.clone();
+    named.kind = kind;
+    named.controls.extend_from_slice(controls);
+    named.targets = targets.into();
+
+    Some(named)
+}
+
+/// Writes `op = e^{i alpha} U(theta, phi, lambda)` for a `2 x 2` unitary,
+/// returning `[theta, phi, lambda, alpha]`.
+fn zyz<T: Float>(op: &Operator<T>) -> [f64; 4] {
+    let to_f64 = |c: Complex<T>| Complex::new(c.re.to_f64().unwrap(), c.im.to_f64().unwrap());
+    let (a, b, c, d) = (
+        to_f64(op[(0, 0)]),
+        to_f64(op[(0, 1)]),
+        to_f64(op[(1, 0)]),
+        to_f64(op[(1, 1)]),
+    );
+
+    let theta = 2. * c.norm().atan2(a.norm());
+    let eps = 1e-12;
+
+    if c.norm() < eps {
+        [0., 0., d.arg() - a.arg(), a.arg()]
+    } else if a.norm() < eps {
+        let alpha = (-b).arg();
+        [theta, c.arg() - alpha, 0., alpha]
+    } else {
+        let alpha = a.arg();
+        [theta, c.arg() - alpha, (-b).arg() - alpha, alpha]
+    }
+}
+
+fn matrix_comment<T: Float>(name: &str, op: &Operator<T>) -> String {
+    let rows = (0..op.rows())
+        .map(|i| {
+            let row = (0..op.cols())
+                .map(|j| {
+                    let (re, im) = (
+                        op[(i, j)].re.to_f64().unwrap(),
+                        op[(i, j)].im.to_f64().unwrap(),
+                    );
+                    format!("{re}{im:+}i")
+                })
+                .collect::<Vec<_>>();
+            format!("[{}]", row.join(", "))
+        })
+        .collect::<Vec<_>>();
+
+    format!("// {name} = [{}]\n", rows.join(", "))
+}
+
+/// Writes a real number so that the importer reads it back, or `None` if it
+/// is not finite. Magnitudes outside `[1e-5, 1e15]` use exponent notation,
+/// since `1e20` would otherwise be written as an integer too long to parse.
+fn number(x: f64) -> Option<String> {
+    if !x.is_finite() {
+        None
+    } else if x != 0. && !(1e-5..=1e15).contains(&x.abs()) {
+        Some(format!("{x:e}"))
+    } else {
+        Some(x.to_string())
+    }
+}
+
+fn list(items: impl IntoIterator<Item = String>) -> String {
+    items.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// Serializes a circuit as an OpenQASM program over one quantum register `q`
+/// and one classical register `c`.
+///
+/// Gates with a standard name in `qelib1.inc` or `stdgates.inc` are written
+/// under that name, as are [`GateKind::Unitary`] matrices exactly equal to a
+/// fixed standard gate such as `cx` or `swap`. Other single-qubit unitaries
+/// get a gate definition built from their `U(theta, phi, lambda)`
+/// decomposition. Anything else is declared `opaque` with its matrix in a
+/// comment for OpenQASM 2.0, and reported as [`CircuitError::Unexportable`]
+/// for OpenQASM 3.0, which has no opaque gates. OpenQASM 2.0 can only
+/// condition on the whole classical register, so other conditions are
+/// reported as [`CircuitError::Unexportable`] too, as are gates with
+/// non-finite parameters.
+pub fn export<T: Float>(
+    circuit: &Circuit<T>,
+    version: QasmVersion,
+) -> Result<String, CircuitError> {
+    let mut defs = String::new();
+    let mut body = String::new();
+
+    let qubit = |q: &usize| format!("q[{q}]");
+
+    for (idx, instruction) in circuit.iter().enumerate() {
+        let named = standard_form(instruction);
+        let instruction = named.as_ref().unwrap_or(instruction);
+
+        let Instruction {
+            kind,
+            targets,
+            controls,
+            params,
+            clbits,
+            condition,
+        } = instruction;
+
+        if let Some(cond) = condition {
+            let whole = cond.clbits.iter().copied().eq(0..circuit.n_clbits());
+
+            match version {
+                QasmVersion::V2 if !whole => return Err(CircuitError::Unexportable(idx)),
+                QasmVersion::V2 => write!(body, "if(c=={}) ", cond.value).unwrap(),
+                QasmVersion::V3 if whole => write!(body, "if (c == {}) ", cond.value).unwrap(),
+                QasmVersion::V3 => {
+                    let bits = cond.clbits.iter().enumerate().map(|(i, c)| {
+                        let negate = if (cond.value >> i) & 1 == 1 { "" } else { "!" };
+                        format!("{negate}c[{c}]")
+                    });
+                    write!(body, "if ({}) ", bits.collect::<Vec<_>>().join(" && ")).unwrap();
+                }
+            }
+        }
+
+        match kind {
+            GateKind::Measure => {
+                let (q, c) = (targets[0], clbits[0]);
+                match version {
+                    QasmVersion::V2 => writeln!(body, "measure q[{q}] -> c[{c}];"),
+                    QasmVersion::V3 => writeln!(body, "c[{c}] = measure q[{q}];"),
+                }
+                .unwrap();
+                continue;
+            }
+            GateKind::Barrier => {
+                writeln!(body, "barrier {};", list(targets.iter().map(qubit))).unwrap();
+                continue;
+            }
+            _ => {}
+        }
+
+        let qubits = list(instruction.qubits().map(|q| qubit(&q)));
+        let mut params = params
+            .iter()
+            .map(|p| number(p.to_f64().unwrap()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(CircuitError::Unexportable(idx))?;
+
+        if let Some(name) = gate_name(kind, controls.len(), version) {
+            if (kind, controls.len(), version) == (&GateKind::U, 1, QasmVersion::V3) {
+                params.push("0".into());
+            }
+
+            let params = if params.is_empty() {
+                String::new()
+            } else {
+                format!("({})", params.join(", "))
+            };
+            writeln!(body, "{name}{params} {qubits};").unwrap();
+            continue;
+        }
+
+        let name = format!("unitary_{idx}");
+        let op = instruction.matrix().unwrap();
+
+        // Controls can be added on top of a named gate or a single-qubit
+        // definition in OpenQASM 3.0, but have to be folded into the matrix
+        // for OpenQASM 2.0.
+        let (op, modifier) = match version {
+            QasmVersion::V3 if !controls.is_empty() && op.rows() == 2 => {
+                if let Some(base) = gate_name(kind, 0, version) {
+                    let params = if params.is_empty() {
+                        String::new()
+                    } else {
+                        format!("({})", params.join(", "))
+                    };
+                    writeln!(body, "ctrl({}) @ {base}{params} {qubits};", controls.len()).unwrap();
+                    continue;
+                }
+                (op, format!("ctrl({}) @ ", controls.len()))
+            }
+            _ => (instruction.controlled_matrix().unwrap(), String::new()),
+        };
+
+        let args = (0..op.rows().trailing_zeros()).map(|i| format!("a{i}"));
+
+        if op.rows() == 2 {
+            let angles = zyz(&op);
+            let [theta, phi, lambda, alpha] = match angles.map(number) {
+                [Some(theta), Some(phi), Some(lambda), Some(alpha)] => [theta, phi, lambda, alpha],
+                _ => return Err(CircuitError::Unexportable(idx)),
+            };
+            let gphase = match version {
+                QasmVersion::V3 if !angles[3].is_zero() => format!(" gphase({alpha});"),
+                _ => String::new(),
+            };
+            writeln!(
+                defs,
+                "gate {name} a0 {{ U({theta}, {phi}, {lambda}) a0;{gphase} }}"
+            )
+            .unwrap();
+        } else if version == QasmVersion::V3 {
+            return Err(CircuitError::Unexportable(idx));
+        } else {
+            defs.push_str(&matrix_comment(&name, &op));
+            writeln!(defs, "opaque {name} {};", list(args)).unwrap();
+        }
+
+        writeln!(body, "{modifier}{name} {qubits};").unwrap();
+    }
+
+    let mut out = String::new();
+    match version {
+        QasmVersion::V2 => {
+            writeln!(out, "OPENQASM 2.0;\ninclude \"qelib1.inc\";").unwrap();
+        }
+        QasmVersion::V3 => {
+            writeln!(out, "OPENQASM 3.0;\ninclude \"stdgates.inc\";").unwrap();
+        }
+    }
+    out.push_str(&defs);
+
+    let (n_qubits, n_clbits) = (circuit.n_qubits(), circuit.n_clbits());
+    match version {
+        QasmVersion::V2 => {
+            writeln!(out, "qreg q[{n_qubits}];").unwrap();
+            if n_clbits > 0 {
+                writeln!(out, "creg c[{n_clbits}];").unwrap();
+            }
+        }
+        QasmVersion::V3 => {
+            writeln!(out, "qubit[{n_qubits}] q;").unwrap();
+            if n_clbits > 0 {
+                writeln!(out, "bit[{n_clbits}] c;").unwrap();
+            }
+        }
+    }
+    out.push_str(&body);
+
+    Ok(out)
+}
+
+impl<T: Float> Circuit<T> {
+    pub fn to_qasm(&self, version: QasmVersion) -> Result<String, CircuitError> {
+        export(self, version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QasmVersion;
+    use crate::prelude::*;
+
+    fn assert_close_up_to_phase(lhs: &Operator<f64>, rhs: &Operator<f64>) {
+        let (i, _) = rhs
+            .inner
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.norm().partial_cmp(&b.1.norm()).unwrap())
+            .unwrap();
+        let phase = rhs.inner[i] / lhs.inner[i];
+
+        assert!(lhs
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a * phase - b).norm() < 1e-9));
+    }
+
+    #[test]
+    fn test_export_named() {
+        let mut circ = Circuit::<f64>::new(2, 2);
+        circ.h(0).cnot(0, 1).phase(0.5, 1).measure(0, 0);
+
+        assert_eq!(
+            circ.to_qasm(QasmVersion::V2).unwrap(),
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg c[2];\n\
+             h q[0];\ncx q[0], q[1];\nu1(0.5) q[1];\nmeasure q[0] -> c[0];\n"
+        );
+        assert_eq!(
+            circ.to_qasm(QasmVersion::V3).unwrap(),
+            "OPENQASM 3.0;\ninclude \"stdgates.inc\";\nqubit[2] q;\nbit[2] c;\n\
+             h q[0];\ncx q[0], q[1];\np(0.5) q[1];\nc[0] = measure q[0];\n"
+        );
+
+        circ.push(
+            Instruction::new(GateKind::X, &[1]).with_condition(Condition {
+                clbits: [0].as_slice().into(),
+                value: 1,
+            }),
+        );
+        assert_eq!(
+            circ.to_qasm(QasmVersion::V2),
+            Err(CircuitError::Unexportable(4))
+        );
+        assert!(circ
+            .to_qasm(QasmVersion::V3)
+            .unwrap()
+            .ends_with("if (c[0]) x q[1];\n"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let op = Operator::from([[cmpx!(0.6), cmpx!(0.8 j)], [cmpx!(0.8 j), cmpx!(0.6)]]);
+        let op = op * Complex::from_polar(1., 0.3);
+
+        let mut circ = Circuit::<f64>::new(3, 0);
+        circ.h(0)
+            .cz(0, 2)
+            .swap(1, 2)
+            .rx(0.25, 1)
+            .u(0.1, 0.2, 0.3, 2)
+            .gate(op.clone(), &[0])
+            .gate(Operator::from([[0., 1.], [1., 0.]]), &[2])
+            .t(1)
+            .push(
+                Instruction::new(GateKind::U, &[2])
+                    .with_controls(&[0])
+                    .with_params(&[0.4, 0.5, 0.6]),
+            );
+
+        let qasm = circ.to_qasm(QasmVersion::V2).unwrap();
+        assert!(qasm.contains("gate unitary_5 a0 { U("));
+
+        let parsed = Circuit::<f64>::from_qasm(&qasm).unwrap();
+        assert_eq!(parsed.len(), circ.len());
+        assert_close_up_to_phase(&parsed.unitary().unwrap(), &circ.unitary().unwrap());
+
+        let mut controlled = Circuit::<f64>::new(2, 0);
+        controlled.push(Instruction::new(GateKind::Unitary(op.clone()), &[1]).with_controls(&[0]));
+
+        let qasm = controlled.to_qasm(QasmVersion::V2).unwrap();
+        assert!(qasm.contains("opaque unitary_0 a0, a1;\n"));
+        assert!(qasm.ends_with("unitary_0 q[0], q[1];\n"));
+
+        let qasm = controlled.to_qasm(QasmVersion::V3).unwrap();
+        assert!(qasm.contains("gphase(0.3); }\n"));
+        assert!(qasm.ends_with("ctrl(1) @ unitary_0 q[0], q[1];\n"));
+
+        let mut wide = Circuit::<f64>::new(2, 0);
+        wide.gate(gates::controlled(&op, 1), &[0, 1]);
+        assert_eq!(
+            wide.to_qasm(QasmVersion::V3),
+            Err(CircuitError::Unexportable(0))
+        );
+    }
+
+    #[test]
+    fn test_export_known_unitaries() {
+        let mut circ = Circuit::<f64>::new(3, 0);
+        circ.gate(gates::cnot(), &[0, 1])
+            .gate(gates::h(), &[2])
+            .gate(gates::swap(), &[2, 0])
+            .gate(gates::controlled(&gates::x(), 2), &[1, 2, 0])
+            .gate(gates::controlled(&gates::swap(), 1), &[2, 0, 1]);
+
+        let body = "cx q[0], q[1];\nh q[2];\nswap q[2], q[0];\nccx q[1], q[2], q[0];\n\
+                    cswap q[2], q[0], q[1];\n";
+        assert!(circ.to_qasm(QasmVersion::V2).unwrap().ends_with(body));
+        assert!(circ.to_qasm(QasmVersion::V3).unwrap().ends_with(body));
+    }
+
+    #[test]
+    fn test_export_params() {
+        let mut circ = Circuit::<f64>::new(1, 0);
+        circ.rz(1e20, 0).rx(-2.5e-9, 0).u(1e15, 0.5, -0., 0);
+
+        for version in [QasmVersion::V2, QasmVersion::V3] {
+            let qasm = circ.to_qasm(version).unwrap();
+            assert!(qasm.contains("rz(1e20) q[0];\n"));
+            assert!(qasm.contains("rx(-2.5e-9) q[0];\n"));
+        }
+
+        let parsed = Circuit::<f64>::from_qasm(&circ.to_qasm(QasmVersion::V2).unwrap()).unwrap();
+        let params = parsed.iter().map(|i| i.params.to_vec()).collect::<Vec<_>>();
+        assert_eq!(params, [vec![1e20], vec![-2.5e-9], vec![1e15, 0.5, 0.]]);
+
+        for bad in [f64::NAN, f64::INFINITY] {
+            let mut circ = Circuit::<f64>::new(1, 0);
+            circ.h(0).rz(bad, 0);
+            for version in [QasmVersion::V2, QasmVersion::V3] {
+                assert_eq!(circ.to_qasm(version), Err(CircuitError::Unexportable(1)));
+            }
+        }
+    }
+}
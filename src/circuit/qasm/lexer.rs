@@ -0,0 +1,160 @@
+use super::{QasmError, QasmErrorKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+    Ident(String),
+    Int(u64),
+    Real(f64),
+    Str(String),
+    Symbol(&'static str),
+    Eof,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "`{s}`"),
+            Token::Int(i) => write!(f, "`{i}`"),
+            Token::Real(r) => write!(f, "`{r}`"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Symbol(s) => write!(f, "`{s}`"),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Spanned {
+    pub token: Token,
+    pub line: usize,
+    pub col: usize,
+}
+
+const SYMBOLS: [&str; 15] = [
+    "->", "==", ";", ",", "(", ")", "[", "]", "{", "}", "+", "-", "*", "/", "^",
+];
+
+pub(super) fn tokenize(src: &str) -> Result<Vec<Spanned>, QasmError> {
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let (mut pos, mut line, mut col) = (0, 1, 1);
+
+    let error = |line, col, kind| QasmError { line, col, kind };
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let (start_line, start_col, start) = (line, col, pos);
+
+        let mut advance = |n: usize, pos: &mut usize| {
+            for _ in 0..n {
+                if chars[*pos] == '\n' {
+                    line += 1;
+                    col = 1;
+                } else {
+                    col += 1;
+                }
+                *pos += 1;
+            }
+        };
+
+        if c.is_whitespace() {
+            advance(1, &mut pos);
+            continue;
+        }
+
+        if c == '/' && chars.get(pos + 1) == Some(&'/') {
+            let len = chars[pos..].iter().take_while(|&&c| c != '\n').count();
+            advance(len, &mut pos);
+            continue;
+        }
+
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            let len = chars[pos..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                .count();
+            advance(len, &mut pos);
+            Token::Ident(chars[start..pos].iter().collect())
+        } else if c.is_ascii_digit()
+            || (c == '.' && matches!(chars.get(pos + 1), Some(d) if d.is_ascii_digit()))
+        {
+            let digits = |from: usize| {
+                chars[from..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count()
+            };
+
+            let mut len = digits(pos);
+            let mut real = false;
+
+            if chars.get(pos + len) == Some(&'.') {
+                real = true;
+                len += 1 + digits(pos + len + 1);
+            }
+
+            if matches!(chars.get(pos + len), Some('e' | 'E')) {
+                let sign = matches!(chars.get(pos + len + 1), Some('+' | '-')) as usize;
+                let exp = digits(pos + len + 1 + sign);
+                if exp > 0 {
+                    real = true;
+                    len += 1 + sign + exp;
+                }
+            }
+
+            advance(len, &mut pos);
+            let text = chars[start..pos].iter().collect::<String>();
+
+            if real {
+                Token::Real(text.parse().unwrap())
+            } else {
+                Token::Int(text.parse().map_err(|_| {
+                    error(start_line, start_col, QasmErrorKind::InvalidNumber(text))
+                })?)
+            }
+        } else if c == '"' {
+            let len = chars[pos + 1..]
+                .iter()
+                .take_while(|&&c| c != '"' && c != '\n')
+                .count();
+            if chars.get(pos + 1 + len) != Some(&'"') {
+                return Err(error(
+                    start_line,
+                    start_col,
+                    QasmErrorKind::UnterminatedString,
+                ));
+            }
+            advance(len + 2, &mut pos);
+            Token::Str(chars[start + 1..pos - 1].iter().collect())
+        } else if let Some(sym) = SYMBOLS.iter().find(|s| {
+            s.chars()
+                .zip(&chars[pos..])
+                .filter(|(a, b)| a == *b)
+                .count()
+                == s.len()
+        }) {
+            advance(sym.len(), &mut pos);
+            Token::Symbol(sym)
+        } else {
+            return Err(error(
+                start_line,
+                start_col,
+                QasmErrorKind::UnexpectedChar(c),
+            ));
+        };
+
+        tokens.push(Spanned {
+            token,
+            line: start_line,
+            col: start_col,
+        });
+    }
+
+    tokens.push(Spanned {
+        token: Token::Eof,
+        line,
+        col,
+    });
+
+    Ok(tokens)
+}
@@ -0,0 +1,334 @@
+pub mod export;
+mod lexer;
+mod parser;
+
+use crate::prelude::*;
+
+use thiserror::Error;
+
+pub use export::{export, QasmVersion};
+
+/// An error raised while reading OpenQASM, pointing at the offending token.
+#[derive(Clone, Debug, Error, PartialEq, Eq, Hash)]
+#[error("line {line}, column {col}: {kind}")]
+pub struct QasmError {
+    pub line: usize,
+    pub col: usize,
+    pub kind: QasmErrorKind,
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq, Hash)]
+pub enum QasmErrorKind {
+    #[error("unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("invalid number `{0}`")]
+    InvalidNumber(String),
+    #[error("unterminated string")]
+    UnterminatedString,
+    #[error("expected {expected}, found {found}")]
+    Expected { expected: String, found: String },
+    #[error("unsupported OpenQASM version {0}")]
+    UnsupportedVersion(String),
+    #[error("cannot include \"{0}\"")]
+    UnsupportedInclude(String),
+    #[error("`{0}` is already defined")]
+    Redefinition(String),
+    #[error("unknown gate `{0}`")]
+    UnknownGate(String),
+    #[error("unknown register `{0}`")]
+    UnknownRegister(String),
+    #[error("unknown identifier `{0}`")]
+    UnknownIdentifier(String),
+    #[error("index {index} is out of range for register `{name}` of size {size}")]
+    IndexOutOfRange {
+        name: String,
+        index: usize,
+        size: usize,
+    },
+    #[error("`{name}` takes {expected} {what}, but {found} were given")]
+    WrongArity {
+        name: String,
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error("a program may declare at most {0} qubits and {0} classical bits")]
+    TooManyBits(usize),
+    #[error("a program may expand to at most {0} operations")]
+    TooManyOperations(usize),
+    #[error("nesting is limited to {0} levels")]
+    NestingTooDeep(usize),
+    #[error("expected {0}, found end of input")]
+    UnexpectedEof(String),
+    #[error("registers of different sizes cannot be used in one statement")]
+    SizeMismatch,
+    #[error("the same qubit is used more than once")]
+    DuplicateQubit,
+    #[error("opaque gate `{0}` has no definition to simulate")]
+    OpaqueGate(String),
+    #[error("`{0}` is not supported")]
+    Unsupported(&'static str),
+}
+
+/// Reads an OpenQASM 2.0 program into a [`Circuit`].
+///
+/// Registers are laid out in declaration order, so the first qubit of the
+/// first `qreg` is qubit `0`. Gates from `qelib1.inc` are mapped onto the
+/// matching [`GateKind`]s, and user-defined gates are expanded inline.
+pub fn parse<T: Float>(src: &str) -> Result<Circuit<T>, QasmError> {
+    parser::Parser::new(src)?.parse()
+}
+
+impl<T: Float> Circuit<T> {
+    pub fn from_qasm(src: &str) -> Result<Self, QasmError> {
+        parse(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QasmError, QasmErrorKind};
+    use crate::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn assert_close(lhs: &Operator<f64>, rhs: &Operator<f64>) {
+        assert_eq!(lhs.shape(), rhs.shape());
+        assert!(lhs
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+    }
+
+    #[test]
+    fn test_parse_qelib1() {
+        let circ = Circuit::<f64>::from_qasm(
+            r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[2];
+            qreg anc[1];
+            creg c[2];
+
+            // Broadcasting over a whole register.
+            h q;
+            cx q[0], anc[0];
+            u2(0, pi) q[1];
+            rz(-pi / 2 + 2 * 0.25e1 ^ 2) anc;
+            barrier q, anc;
+            measure q -> c;
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(circ.n_qubits(), 3);
+        assert_eq!(circ.n_clbits(), 2);
+
+        let kinds = circ.iter().map(|i| i.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                GateKind::H,
+                GateKind::H,
+                GateKind::X,
+                GateKind::U,
+                GateKind::Rz,
+                GateKind::Barrier,
+                GateKind::Measure,
+                GateKind::Measure,
+            ]
+        );
+
+        let cx = &circ.instructions()[2];
+        assert_eq!((&cx.controls[..], &cx.targets[..]), (&[0][..], &[2][..]));
+
+        let u2 = &circ.instructions()[3];
+        assert_eq!(
+            &u2.params[..],
+            [std::f64::consts::FRAC_PI_2, 0., std::f64::consts::PI]
+        );
+
+        let rz = &circ.instructions()[4];
+        assert!((rz.params[0] - (12.5 - std::f64::consts::FRAC_PI_2)).abs() < 1e-12);
+
+        let measure = &circ.instructions()[7];
+        assert_eq!(
+            (&measure.targets[..], &measure.clbits[..]),
+            (&[1][..], &[1][..])
+        );
+    }
+
+    #[test]
+    fn test_custom_gates() {
+        let circ = Circuit::<f64>::from_qasm(
+            r#"
+            OPENQASM 2.0;
+            qreg q[2];
+            gate h a { U(pi/2, 0, pi) a; }
+            gate bell(theta) a, b {
+                h a;
+                CX a, b;
+                U(theta, 0, 0) b;
+            }
+            bell(0) q[0], q[1];
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(circ.len(), 3);
+
+        let amp = 0.5f64.sqrt();
+        let ket = circ
+            .run_ket(Ket::from([1., 0., 0., 0.].as_slice()))
+            .unwrap();
+        assert!(ket
+            .inner
+            .iter()
+            .zip([amp, 0., 0., amp])
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+
+        let swap = Circuit::<f64>::from_qasm(
+            "OPENQASM 2.0; include \"qelib1.inc\"; qreg q[2]; cx q[0],q[1]; cx q[1],q[0]; cx q[0],q[1];",
+        )
+        .unwrap();
+        let mut expected = Circuit::<f64>::new(2, 0);
+        expected.swap(0, 1);
+        assert_close(&swap.unitary().unwrap(), &expected.unitary().unwrap());
+
+        let rzz = Circuit::<f64>::from_qasm(
+            "OPENQASM 2.0; include \"qelib1.inc\"; qreg q[2]; rzz(0.3) q[0], q[1];",
+        )
+        .unwrap();
+        assert_eq!(rzz.len(), 3);
+
+        let barrier = Circuit::<f64>::from_qasm(
+            "OPENQASM 2.0; qreg q[1]; gate g a { barrier a, a; } g q[0];",
+        )
+        .unwrap();
+        assert_eq!(barrier.instructions()[0].targets[..], [0]);
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let circ = Circuit::<f64>::from_qasm(
+            r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[2];
+            creg c[1];
+            creg d[1];
+            x q[0];
+            measure q[0] -> c[0];
+            if (c == 1) x q[1];
+            measure q[1] -> d[0];
+            "#,
+        )
+        .unwrap();
+
+        let clbits = circ
+            .execute(&mut StateVector::new(2), &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(clbits, [true, true]);
+        assert!(circ.inverse().is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |src: &str| Circuit::<f64>::from_qasm(src).unwrap_err();
+
+        assert_eq!(
+            err("OPENQASM 2.0;\nqreg q[1];\nh q[0];"),
+            QasmError {
+                line: 3,
+                col: 1,
+                kind: QasmErrorKind::UnknownGate("h".into())
+            }
+        );
+        assert_eq!(
+            err("OPENQASM 2.0;\nqreg q[1];\n  U(0, 0, 0) q[1];").kind,
+            QasmErrorKind::IndexOutOfRange {
+                name: "q".into(),
+                index: 1,
+                size: 1
+            }
+        );
+        assert_eq!(
+            err("OPENQASM 3.0;").kind,
+            QasmErrorKind::UnsupportedVersion("3".into())
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; qreg q[2]; CX q[0], q[0];").kind,
+            QasmErrorKind::DuplicateQubit
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; qreg q[2]; qreg r[3]; CX q, r;").kind,
+            QasmErrorKind::SizeMismatch
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; gate g(a) q { U(b, 0, 0) q; }").kind,
+            QasmErrorKind::UnknownIdentifier("b".into())
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; qreg q[1]; opaque g q; g q[0];").kind,
+            QasmErrorKind::OpaqueGate("g".into())
+        );
+        assert_eq!(
+            err("OPENQASM 2.0;\nqreg q[18446744073709551615];"),
+            QasmError {
+                line: 2,
+                col: 8,
+                kind: QasmErrorKind::TooManyBits(1 << 20)
+            }
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; creg c[1048576]; creg d[1];").kind,
+            QasmErrorKind::TooManyBits(1 << 20)
+        );
+
+        let e = err("OPENQASM 2.0;\nqreg q[1]\ncreg c[1];");
+        assert_eq!((e.line, e.col), (3, 1));
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 1: expected `;`, found `creg`"
+        );
+        assert_eq!(
+            err("OPENQASM"),
+            QasmError {
+                line: 1,
+                col: 9,
+                kind: QasmErrorKind::UnexpectedEof("a version number".into())
+            }
+        );
+        assert_eq!(
+            err("OPENQASM 2.0;\ninclude").kind,
+            QasmErrorKind::UnexpectedEof("a file name".into())
+        );
+        assert_eq!(
+            err("OPENQASM 2.0; gate h a { U(0, 0, 0) a; } include \"qelib1.inc\";").kind,
+            QasmErrorKind::Redefinition("h".into())
+        );
+
+        let parens = format!("{}1{}", "(".repeat(1000), ")".repeat(1000));
+        let negs = format!("{}1", "-".repeat(1000));
+        for expr in [parens, negs] {
+            let src = format!("OPENQASM 2.0; qreg q[1]; U({}, 0, 0) q[0];", expr);
+            assert_eq!(err(&src).kind, QasmErrorKind::NestingTooDeep(128));
+        }
+        let shallow = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        let src = format!(
+            "OPENQASM 2.0; qreg q[1]; U({}, -(-1), 1 + 2 * 3) q[0];",
+            shallow
+        );
+        assert!(Circuit::<f64>::from_qasm(&src).is_ok());
+
+        // Each gate doubles the previous one, so `g20` expands to 2^21 operations.
+        let mut src =
+            String::from("OPENQASM 2.0; qreg q[1]; gate g0 a { U(0, 0, 0) a; U(0, 0, 0) a; }");
+        for i in 1..=20 {
+            src += &format!(" gate g{} a {{ g{} a; g{} a; }}", i, i - 1, i - 1);
+        }
+        src += " g20 q[0];";
+        assert_eq!(err(&src).kind, QasmErrorKind::TooManyOperations(1 << 20));
+    }
+}
@@ -0,0 +1,967 @@
+use crate::prelude::*;
+
+use std::collections::HashMap;
+
+use super::lexer::{tokenize, Spanned, Token};
+use super::{QasmError, QasmErrorKind};
+use crate::circuit::Condition;
+
+/// The gates of `qelib1.inc` that have no matching [`GateKind`], written in
+/// terms of those that do.
+const QELIB1: &str = r#"
+gate u0(gamma) q { U(0, 0, 0) q; }
+gate sx a { sdg a; h a; sdg a; }
+gate sxdg a { s a; h a; s a; }
+gate csx a, b { h b; cu1(pi / 2) a, b; h b; }
+gate rzz(theta) a, b { cx a, b; u1(theta) b; cx a, b; }
+gate rxx(theta) a, b { u3(pi / 2, theta, 0) a; h b; cx a, b; u1(-theta) b; cx a, b; h b; u2(-pi, pi - theta) a; }
+gate cu(theta, phi, lambda, gamma) c, t {
+    p(gamma) c;
+    p((lambda + phi) / 2) c;
+    p((lambda - phi) / 2) t;
+    cx c, t;
+    u(-theta / 2, 0, -(phi + lambda) / 2) t;
+    cx c, t;
+    u(theta / 2, phi, 0) t;
+}
+"#;
+
+/// The gate kind, number of controls and number of parameters of a gate that
+/// maps directly onto an [`Instruction`]. Only `U` and `CX` are available
+/// without including `qelib1.inc`.
+fn native<T: Float>(name: &str, qelib1: bool) -> Option<(GateKind<T>, usize, usize)> {
+    Some(match (name, qelib1) {
+        ("U", _) => (GateKind::U, 0, 3),
+        ("CX", _) => (GateKind::X, 1, 0),
+        (_, false) => return None,
+        ("u3" | "u", _) => (GateKind::U, 0, 3),
+        ("u2", _) => (GateKind::U, 0, 2),
+        ("u1" | "p", _) => (GateKind::Phase, 0, 1),
+        ("id", _) => (GateKind::I, 0, 0),
+        ("x", _) => (GateKind::X, 0, 0),
+        ("y", _) => (GateKind::Y, 0, 0),
+        ("z", _) => (GateKind::Z, 0, 0),
+        ("h", _) => (GateKind::H, 0, 0),
+        ("s", _) => (GateKind::S, 0, 0),
+        ("sdg", _) => (GateKind::Sdg, 0, 0),
+        ("t", _) => (GateKind::T, 0, 0),
+        ("tdg", _) => (GateKind::Tdg, 0, 0),
+        ("rx", _) => (GateKind::Rx, 0, 1),
+        ("ry", _) => (GateKind::Ry, 0, 1),
+        ("rz", _) => (GateKind::Rz, 0, 1),
+        ("swap", _) => (GateKind::Swap, 0, 0),
+        ("cx", _) => (GateKind::X, 1, 0),
+        ("cy", _) => (GateKind::Y, 1, 0),
+        ("cz", _) => (GateKind::Z, 1, 0),
+        ("ch", _) => (GateKind::H, 1, 0),
+        ("ccx", _) => (GateKind::X, 2, 0),
+        ("cswap", _) => (GateKind::Swap, 1, 0),
+        ("crx", _) => (GateKind::Rx, 1, 1),
+        ("cry", _) => (GateKind::Ry, 1, 1),
+        ("crz", _) => (GateKind::Rz, 1, 1),
+        ("cu1" | "cp", _) => (GateKind::Phase, 1, 1),
+        ("cu3", _) => (GateKind::U, 1, 3),
+        _ => return None,
+    })
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(f64),
+    Param(String),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, env: &HashMap<&str, f64>) -> f64 {
+        match self {
+            Expr::Num(x) => *x,
+            Expr::Param(p) => env[p.as_str()],
+            Expr::Neg(e) => -e.eval(env),
+            Expr::Binary(op, lhs, rhs) => {
+                let (a, b) = (lhs.eval(env), rhs.eval(env));
+                match *op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    _ => a.powf(b),
+                }
+            }
+            Expr::Call(f, e) => {
+                let x = e.eval(env);
+                match f.as_str() {
+                    "sin" => x.sin(),
+                    "cos" => x.cos(),
+                    "tan" => x.tan(),
+                    "exp" => x.exp(),
+                    "ln" => x.ln(),
+                    _ => x.sqrt(),
+                }
+            }
+        }
+    }
+
+    /// The first parameter used by the expression that is not in `known`.
+    fn unknown(&self, known: &[String]) -> Option<&str> {
+        match self {
+            Expr::Num(_) => None,
+            Expr::Param(p) => (!known.contains(p)).then_some(p.as_str()),
+            Expr::Neg(e) | Expr::Call(_, e) => e.unknown(known),
+            Expr::Binary(_, lhs, rhs) => lhs.unknown(known).or_else(|| rhs.unknown(known)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum BodyOp {
+    Gate {
+        name: String,
+        params: Vec<Expr>,
+        args: Vec<usize>,
+    },
+    Barrier(Vec<usize>),
+}
+
+/// A user-defined gate, with arguments referred to by position. Opaque gates
+/// have no body.
+#[derive(Clone, Debug)]
+struct GateDef {
+    n_params: usize,
+    n_qubits: usize,
+    body: Option<Vec<BodyOp>>,
+}
+
+/// The most qubits, and separately the most classical bits, a program may
+/// declare. Whole-register arguments are expanded into one index per bit, so
+/// this also bounds the size of each argument list.
+const MAX_BITS: usize = 1 << 20;
+
+/// The most operations a program may expand to, counting every gate call in
+/// the bodies of user-defined gates, so that nested definitions cannot blow
+/// up exponentially.
+const MAX_OPERATIONS: usize = 1 << 20;
+
+/// The deepest nesting of expressions or gate expansions. Both are handled
+/// recursively, so this keeps untrusted input from overflowing the stack.
+const MAX_NESTING: usize = 128;
+
+#[derive(Clone, Copy, Debug)]
+struct Register {
+    offset: usize,
+    size: usize,
+}
+
+/// A qubit or classical argument: a whole register or one of its elements.
+struct Argument {
+    name: String,
+    index: Option<usize>,
+    line: usize,
+    col: usize,
+}
+
+pub(super) struct Parser<T: Float> {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    qelib1: bool,
+    qregs: HashMap<String, Register>,
+    cregs: HashMap<String, Register>,
+    gates: HashMap<String, GateDef>,
+    n_qubits: usize,
+    n_clbits: usize,
+    instructions: Vec<Instruction<T>>,
+    /// Operations expanded so far, counted against `MAX_OPERATIONS`.
+    operations: usize,
+    /// Current nesting of expressions or gate expansions, counted against
+    /// `MAX_NESTING`.
+    depth: usize,
+}
+
+impl<T: Float> Parser<T> {
+    pub(super) fn new(src: &str) -> Result<Self, QasmError> {
+        Ok(Self {
+            tokens: tokenize(src)?,
+            pos: 0,
+            qelib1: false,
+            qregs: HashMap::new(),
+            cregs: HashMap::new(),
+            gates: HashMap::new(),
+            n_qubits: 0,
+            n_clbits: 0,
+            instructions: Vec::new(),
+            operations: 0,
+            depth: 0,
+        })
+    }
+
+    pub(super) fn parse(mut self) -> Result<Circuit<T>, QasmError> {
+        self.header()?;
+
+        while self.peek().token != Token::Eof {
+            self.statement()?;
+        }
+
+        let mut circuit = Circuit::new(self.n_qubits, self.n_clbits);
+        circuit.extend(self.instructions);
+
+        Ok(circuit)
+    }
+
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Spanned {
+        let token = self.tokens[self.pos].clone();
+        if token.token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_at(spanned: &Spanned, kind: QasmErrorKind) -> QasmError {
+        QasmError {
+            line: spanned.line,
+            col: spanned.col,
+            kind,
+        }
+    }
+
+    fn expected<U>(&self, expected: &str) -> Result<U, QasmError> {
+        let found = self.peek();
+        let kind = match found.token {
+            Token::Eof => QasmErrorKind::UnexpectedEof(expected.into()),
+            _ => QasmErrorKind::Expected {
+                expected: expected.into(),
+                found: found.token.to_string(),
+            },
+        };
+        Err(Self::error_at(found, kind))
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek().token, Token::Symbol(s) if s == symbol)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), QasmError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.expected(&format!("`{symbol}`"))
+        }
+    }
+
+    /// Counts one more expanded operation against `MAX_OPERATIONS`.
+    fn count_operation(&mut self) -> Result<(), QasmErrorKind> {
+        self.operations += 1;
+        if self.operations > MAX_OPERATIONS {
+            return Err(QasmErrorKind::TooManyOperations(MAX_OPERATIONS));
+        }
+        Ok(())
+    }
+
+    /// Enters one more level of nesting, failing past `MAX_NESTING`. Callers
+    /// restore `self.depth` when they are done.
+    fn nest(&mut self) -> Result<(), QasmErrorKind> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(QasmErrorKind::NestingTooDeep(MAX_NESTING));
+        }
+        Ok(())
+    }
+
+    fn nest_at(&mut self) -> Result<(), QasmError> {
+        let at = self.peek().clone();
+        self.nest().map_err(|kind| Self::error_at(&at, kind))
+    }
+
+    fn ident(&mut self) -> Result<(String, Spanned), QasmError> {
+        match &self.peek().token {
+            Token::Ident(name) => {
+                let name = name.clone();
+                Ok((name, self.next()))
+            }
+            _ => self.expected("an identifier"),
+        }
+    }
+
+    fn int(&mut self) -> Result<usize, QasmError> {
+        match self.peek().token {
+            Token::Int(i) => {
+                self.next();
+                Ok(i as usize)
+            }
+            _ => self.expected("an integer"),
+        }
+    }
+
+    fn header(&mut self) -> Result<(), QasmError> {
+        match self.ident()? {
+            (name, _) if name == "OPENQASM" => {}
+            (_, spanned) => {
+                return Err(Self::error_at(
+                    &spanned,
+                    QasmErrorKind::Expected {
+                        expected: "`OPENQASM`".into(),
+                        found: spanned.token.to_string(),
+                    },
+                ))
+            }
+        }
+
+        let version = self.peek().clone();
+        match version.token {
+            Token::Real(2.0) | Token::Int(2) => {}
+            Token::Real(_) | Token::Int(_) => {
+                let text = version.token.to_string().trim_matches('`').to_string();
+                return Err(Self::error_at(
+                    &version,
+                    QasmErrorKind::UnsupportedVersion(text),
+                ));
+            }
+            _ => return self.expected("a version number"),
+        }
+        self.next();
+
+        self.expect(";")
+    }
+
+    fn statement(&mut self) -> Result<(), QasmError> {
+        let keyword = match &self.peek().token {
+            Token::Ident(name) => name.clone(),
+            _ => return self.expected("a statement"),
+        };
+
+        match keyword.as_str() {
+            "include" => self.include(),
+            "qreg" | "creg" => self.register(),
+            "gate" => self.gate_def(false),
+            "opaque" => self.gate_def(true),
+            "barrier" => {
+                let spanned = self.next();
+                let qubits = self
+                    .arguments()?
+                    .iter()
+                    .map(|a| self.resolve(a, false))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+                self.expect(";")?;
+                self.count_operation()
+                    .map_err(|kind| Self::error_at(&spanned, kind))?;
+                self.instructions
+                    .push(Instruction::new(GateKind::Barrier, &dedup(qubits)));
+                Ok(())
+            }
+            "if" => {
+                let spanned = self.next();
+                self.expect("(")?;
+                let (name, creg) = self.ident()?;
+                self.expect("==")?;
+                let value = self.int()? as u64;
+                self.expect(")")?;
+
+                let reg = *self.cregs.get(&name).ok_or_else(|| {
+                    Self::error_at(&creg, QasmErrorKind::UnknownRegister(name.clone()))
+                })?;
+                let condition = Condition {
+                    clbits: (reg.offset..reg.offset + reg.size).collect(),
+                    value,
+                };
+
+                match &self.peek().token {
+                    Token::Ident(s) if s == "if" || s == "barrier" => Err(Self::error_at(
+                        &spanned,
+                        QasmErrorKind::Unsupported("nested if"),
+                    )),
+                    _ => self.operation(Some(condition)),
+                }
+            }
+            _ => self.operation(None),
+        }
+    }
+
+    fn include(&mut self) -> Result<(), QasmError> {
+        self.next();
+
+        let spanned = self.peek().clone();
+        let file = match &spanned.token {
+            Token::Str(file) => file.clone(),
+            _ => return self.expected("a file name"),
+        };
+        self.next();
+        self.expect(";")?;
+
+        if file != "qelib1.inc" {
+            return Err(Self::error_at(
+                &spanned,
+                QasmErrorKind::UnsupportedInclude(file),
+            ));
+        }
+
+        if !self.qelib1 {
+            let mut lib = tokenize(QELIB1).expect("qelib1 definitions are valid");
+            lib.pop();
+
+            // Gates defined earlier would otherwise be shadowed by, or clash
+            // with, the library's own definitions.
+            let defined = lib
+                .windows(2)
+                .filter_map(|w| match (&w[0].token, &w[1].token) {
+                    (Token::Ident(g), Token::Ident(name)) if g == "gate" => Some(name),
+                    _ => None,
+                });
+            if let Some(name) = self
+                .gates
+                .keys()
+                .find(|&g| native::<T>(g, true).is_some() || defined.clone().any(|d| d == g))
+            {
+                return Err(Self::error_at(
+                    &spanned,
+                    QasmErrorKind::Redefinition(name.clone()),
+                ));
+            }
+
+            self.qelib1 = true;
+            self.tokens.splice(self.pos..self.pos, lib);
+        }
+
+        Ok(())
+    }
+
+    fn register(&mut self) -> Result<(), QasmError> {
+        let (keyword, _) = self.ident()?;
+        let (name, spanned) = self.ident()?;
+        self.expect("[")?;
+        let size_at = self.peek().clone();
+        let size = self.int()?;
+        self.expect("]")?;
+        self.expect(";")?;
+
+        if self.qregs.contains_key(&name) || self.cregs.contains_key(&name) {
+            return Err(Self::error_at(&spanned, QasmErrorKind::Redefinition(name)));
+        }
+
+        let total = if keyword == "qreg" {
+            &mut self.n_qubits
+        } else {
+            &mut self.n_clbits
+        };
+        let offset = *total;
+        *total = offset
+            .checked_add(size)
+            .filter(|&n| n <= MAX_BITS)
+            .ok_or_else(|| Self::error_at(&size_at, QasmErrorKind::TooManyBits(MAX_BITS)))?;
+
+        let register = Register { offset, size };
+        if keyword == "qreg" {
+            self.qregs.insert(name, register);
+        } else {
+            self.cregs.insert(name, register);
+        }
+
+        Ok(())
+    }
+
+    fn gate_def(&mut self, opaque: bool) -> Result<(), QasmError> {
+        self.next();
+        let (name, spanned) = self.ident()?;
+
+        if self.gates.contains_key(&name) || native::<T>(&name, self.qelib1).is_some() {
+            return Err(Self::error_at(&spanned, QasmErrorKind::Redefinition(name)));
+        }
+
+        let params = if self.eat("(") {
+            let params = if self.is_symbol(")") {
+                Vec::new()
+            } else {
+                self.idents()?
+            };
+            self.expect(")")?;
+            params
+        } else {
+            Vec::new()
+        };
+        let qargs = self.idents()?;
+
+        let body = if opaque {
+            self.expect(";")?;
+            None
+        } else {
+            self.expect("{")?;
+            let mut body = Vec::new();
+
+            while !self.eat("}") {
+                body.push(self.body_op(&params, &qargs)?);
+            }
+
+            Some(body)
+        };
+
+        self.gates.insert(
+            name,
+            GateDef {
+                n_params: params.len(),
+                n_qubits: qargs.len(),
+                body,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn body_op(&mut self, params: &[String], qargs: &[String]) -> Result<BodyOp, QasmError> {
+        let (name, spanned) = self.ident()?;
+
+        let exprs = if name != "barrier" && self.eat("(") {
+            let exprs = self.expressions(params)?;
+            self.expect(")")?;
+            exprs
+        } else {
+            Vec::new()
+        };
+
+        let args =
+            self.idents_spanned()?
+                .into_iter()
+                .map(|(arg, spanned)| {
+                    qargs.iter().position(|q| *q == arg).ok_or_else(|| {
+                        Self::error_at(&spanned, QasmErrorKind::UnknownIdentifier(arg))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        self.expect(";")?;
+
+        if name == "barrier" {
+            return Ok(BodyOp::Barrier(args));
+        }
+
+        let n_qubits = match native::<T>(&name, self.qelib1) {
+            Some((kind, controls, _)) => kind.n_targets().unwrap() + controls,
+            None => match self.gates.get(&name) {
+                Some(def) => def.n_qubits,
+                None => return Err(Self::error_at(&spanned, QasmErrorKind::UnknownGate(name))),
+            },
+        };
+
+        if dedup(args.clone()).len() != args.len() {
+            return Err(Self::error_at(&spanned, QasmErrorKind::DuplicateQubit));
+        }
+
+        if args.len() != n_qubits {
+            return Err(Self::error_at(
+                &spanned,
+                QasmErrorKind::WrongArity {
+                    name,
+                    what: "qubits",
+                    expected: n_qubits,
+                    found: args.len(),
+                },
+            ));
+        }
+
+        Ok(BodyOp::Gate {
+            name,
+            params: exprs,
+            args,
+        })
+    }
+
+    fn idents(&mut self) -> Result<Vec<String>, QasmError> {
+        Ok(self.idents_spanned()?.into_iter().map(|(s, _)| s).collect())
+    }
+
+    fn idents_spanned(&mut self) -> Result<Vec<(String, Spanned)>, QasmError> {
+        let mut idents = vec![self.ident()?];
+        while self.eat(",") {
+            idents.push(self.ident()?);
+        }
+        Ok(idents)
+    }
+
+    fn argument(&mut self) -> Result<Argument, QasmError> {
+        let (name, spanned) = self.ident()?;
+
+        let index = if self.eat("[") {
+            let index = self.int()?;
+            self.expect("]")?;
+            Some(index)
+        } else {
+            None
+        };
+
+        Ok(Argument {
+            name,
+            index,
+            line: spanned.line,
+            col: spanned.col,
+        })
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Argument>, QasmError> {
+        let mut args = vec![self.argument()?];
+        while self.eat(",") {
+            args.push(self.argument()?);
+        }
+        Ok(args)
+    }
+
+    /// The bits an argument refers to, in register order.
+    fn resolve(&self, arg: &Argument, classical: bool) -> Result<Vec<usize>, QasmError> {
+        let error = |kind| QasmError {
+            line: arg.line,
+            col: arg.col,
+            kind,
+        };
+
+        let regs = if classical { &self.cregs } else { &self.qregs };
+        let reg = regs
+            .get(&arg.name)
+            .ok_or_else(|| error(QasmErrorKind::UnknownRegister(arg.name.clone())))?;
+
+        match arg.index {
+            Some(index) if index >= reg.size => Err(error(QasmErrorKind::IndexOutOfRange {
+                name: arg.name.clone(),
+                index,
+                size: reg.size,
+            })),
+            Some(index) => Ok(vec![reg.offset + index]),
+            None => Ok((reg.offset..reg.offset + reg.size).collect()),
+        }
+    }
+
+    /// Expands whole-register arguments into one set of bits per element.
+    fn broadcast(
+        &self,
+        args: &[Argument],
+        classical: &[bool],
+    ) -> Result<Vec<Vec<usize>>, QasmError> {
+        let resolved = args
+            .iter()
+            .zip(classical)
+            .map(|(a, &c)| self.resolve(a, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut size = None;
+        for (arg, bits) in args.iter().zip(&resolved) {
+            if arg.index.is_none() && matches!(size.replace(bits.len()), Some(s) if s != bits.len())
+            {
+                return Err(QasmError {
+                    line: arg.line,
+                    col: arg.col,
+                    kind: QasmErrorKind::SizeMismatch,
+                });
+            }
+        }
+
+        Ok((0..size.unwrap_or(1))
+            .map(|i| {
+                args.iter()
+                    .zip(&resolved)
+                    .map(|(a, bits)| if a.index.is_none() { bits[i] } else { bits[0] })
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn operation(&mut self, condition: Option<Condition>) -> Result<(), QasmError> {
+        let (name, spanned) = self.ident()?;
+
+        match name.as_str() {
+            "measure" => {
+                let qubit = self.argument()?;
+                self.expect("->")?;
+                let clbit = self.argument()?;
+                self.expect(";")?;
+
+                for bits in self.broadcast(&[qubit, clbit], &[false, true])? {
+                    self.count_operation()
+                        .map_err(|kind| Self::error_at(&spanned, kind))?;
+                    let mut instruction =
+                        Instruction::new(GateKind::Measure, &bits[..1]).with_clbits(&bits[1..]);
+                    instruction.condition = condition.clone();
+                    self.instructions.push(instruction);
+                }
+
+                Ok(())
+            }
+            "reset" => Err(Self::error_at(
+                &spanned,
+                QasmErrorKind::Unsupported("reset"),
+            )),
+            _ => {
+                let exprs = if self.eat("(") {
+                    let exprs = self.expressions(&[])?;
+                    self.expect(")")?;
+                    exprs
+                } else {
+                    Vec::new()
+                };
+                let args = self.arguments()?;
+                self.expect(";")?;
+
+                let params = exprs
+                    .iter()
+                    .map(|e| e.eval(&HashMap::new()))
+                    .collect::<Vec<_>>();
+
+                for qubits in self.broadcast(&args, &vec![false; args.len()])? {
+                    self.apply(&name, &params, &qubits, &condition)
+                        .map_err(|kind| Self::error_at(&spanned, kind))?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Appends the instructions for one gate, expanding user-defined gates.
+    fn apply(
+        &mut self,
+        name: &str,
+        params: &[f64],
+        qubits: &[usize],
+        condition: &Option<Condition>,
+    ) -> Result<(), QasmErrorKind> {
+        let arity = |what, expected: usize, found: usize| {
+            if expected == found {
+                Ok(())
+            } else {
+                Err(QasmErrorKind::WrongArity {
+                    name: name.into(),
+                    what,
+                    expected,
+                    found,
+                })
+            }
+        };
+
+        if dedup(qubits.to_vec()).len() != qubits.len() {
+            return Err(QasmErrorKind::DuplicateQubit);
+        }
+        self.count_operation()?;
+
+        if let Some((kind, n_controls, n_params)) = native::<T>(name, self.qelib1) {
+            arity("parameters", n_params, params.len())?;
+            arity(
+                "qubits",
+                kind.n_targets().unwrap() + n_controls,
+                qubits.len(),
+            )?;
+
+            let mut params = params
+                .iter()
+                .map(|&p| T::from(p).unwrap())
+                .collect::<Vec<_>>();
+            if kind == GateKind::U && params.len() == 2 {
+                params.insert(0, T::from(std::f64::consts::FRAC_PI_2).unwrap());
+            }
+
+            let mut instruction = Instruction::new(kind, &qubits[n_controls..])
+                .with_controls(&qubits[..n_controls])
+                .with_params(&params);
+            instruction.condition = condition.clone();
+            self.instructions.push(instruction);
+
+            return Ok(());
+        }
+
+        let def = self
+            .gates
+            .get(name)
+            .cloned()
+            .ok_or_else(|| QasmErrorKind::UnknownGate(name.into()))?;
+
+        arity("parameters", def.n_params, params.len())?;
+        arity("qubits", def.n_qubits, qubits.len())?;
+
+        let body = def
+            .body
+            .ok_or_else(|| QasmErrorKind::OpaqueGate(name.into()))?;
+
+        // Parameters are positional, so give them their index as a name.
+        let names = (0..params.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+        let env = names
+            .iter()
+            .map(String::as_str)
+            .zip(params.iter().copied())
+            .collect::<HashMap<_, _>>();
+
+        self.nest()?;
+        for op in body {
+            match op {
+                BodyOp::Gate {
+                    name,
+                    params: exprs,
+                    args,
+                } => {
+                    let params = exprs.iter().map(|e| e.eval(&env)).collect::<Vec<_>>();
+                    let args = args.iter().map(|&a| qubits[a]).collect::<Vec<_>>();
+                    self.apply(&name, &params, &args, condition)?;
+                }
+                BodyOp::Barrier(args) => {
+                    self.count_operation()?;
+                    let args = args.iter().map(|&a| qubits[a]).collect::<Vec<_>>();
+                    self.instructions
+                        .push(Instruction::new(GateKind::Barrier, &dedup(args)));
+                }
+            }
+        }
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn expressions(&mut self, params: &[String]) -> Result<Vec<Expr>, QasmError> {
+        let mut exprs = vec![self.checked_expression(params)?];
+        while self.eat(",") {
+            exprs.push(self.checked_expression(params)?);
+        }
+        Ok(exprs)
+    }
+
+    /// Parses an expression, renaming gate parameters to their position and
+    /// rejecting any other identifier.
+    fn checked_expression(&mut self, params: &[String]) -> Result<Expr, QasmError> {
+        let start = self.peek().clone();
+        let expr = self.expression()?;
+
+        if let Some(unknown) = expr.unknown(params) {
+            return Err(Self::error_at(
+                &start,
+                QasmErrorKind::UnknownIdentifier(unknown.into()),
+            ));
+        }
+
+        Ok(rename(expr, params))
+    }
+
+    // Every operator and parenthesis counts as a level of nesting, even in a
+    // flat chain like `1 + 1 + 1`, since it still deepens the tree that
+    // `Expr::eval` recurses over.
+    fn expression(&mut self) -> Result<Expr, QasmError> {
+        let depth = self.depth;
+        let mut lhs = self.term()?;
+
+        loop {
+            let op = match () {
+                _ if self.eat("+") => "+",
+                _ if self.eat("-") => "-",
+                _ => {
+                    self.depth = depth;
+                    return Ok(lhs);
+                }
+            };
+            self.nest_at()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, QasmError> {
+        let depth = self.depth;
+        let mut lhs = self.unary()?;
+
+        loop {
+            let op = match () {
+                _ if self.eat("*") => "*",
+                _ if self.eat("/") => "/",
+                _ => {
+                    self.depth = depth;
+                    return Ok(lhs);
+                }
+            };
+            self.nest_at()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, QasmError> {
+        let depth = self.depth;
+
+        let expr = if self.eat("-") {
+            self.nest_at()?;
+            Expr::Neg(Box::new(self.unary()?))
+        } else {
+            let base = self.atom()?;
+
+            if self.eat("^") {
+                self.nest_at()?;
+                Expr::Binary("^", Box::new(base), Box::new(self.unary()?))
+            } else {
+                base
+            }
+        };
+
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, QasmError> {
+        match self.peek().token.clone() {
+            Token::Int(i) => {
+                self.next();
+                Ok(Expr::Num(i as f64))
+            }
+            Token::Real(r) => {
+                self.next();
+                Ok(Expr::Num(r))
+            }
+            Token::Symbol("(") => {
+                self.next();
+                self.nest_at()?;
+                let expr = self.expression()?;
+                self.expect(")")?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Token::Ident(name) => {
+                self.next();
+
+                if name == "pi" {
+                    return Ok(Expr::Num(std::f64::consts::PI));
+                }
+
+                if matches!(name.as_str(), "sin" | "cos" | "tan" | "exp" | "ln" | "sqrt") {
+                    self.expect("(")?;
+                    self.nest_at()?;
+                    let expr = self.expression()?;
+                    self.expect(")")?;
+                    self.depth -= 1;
+                    return Ok(Expr::Call(name, Box::new(expr)));
+                }
+
+                Ok(Expr::Param(name))
+            }
+            _ => self.expected("an expression"),
+        }
+    }
+}
+
+fn rename(expr: Expr, params: &[String]) -> Expr {
+    match expr {
+        Expr::Param(p) => Expr::Param(params.iter().position(|q| *q == p).unwrap().to_string()),
+        Expr::Neg(e) => Expr::Neg(Box::new(rename(*e, params))),
+        Expr::Call(f, e) => Expr::Call(f, Box::new(rename(*e, params))),
+        Expr::Binary(op, lhs, rhs) => Expr::Binary(
+            op,
+            Box::new(rename(*lhs, params)),
+            Box::new(rename(*rhs, params)),
+        ),
+        num => num,
+    }
+}
+
+fn dedup(mut bits: Vec<usize>) -> Vec<usize> {
+    bits.sort_unstable();
+    bits.dedup();
+    bits
+}
@@ -0,0 +1,128 @@
+use crate::prelude::*;
+
+use num::Zero;
+
+/// Tolerance-aware equality, in the spirit of the `approx` crate.
+///
+/// Two values are `abs_diff_eq` if every pair of entries is within `epsilon`
+/// of each other, and `relative_eq` if every pair is either within `epsilon`
+/// or within `max_relative` of the larger modulus. Values of different shapes
+/// are never equal.
+pub trait ApproxEq<T: Float> {
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool;
+
+    fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool;
+
+    /// `relative_eq` with both tolerances set to the square root of the
+    /// machine epsilon, loose enough to absorb rounding in gate products.
+    fn approx_eq(&self, other: &Self) -> bool {
+        let tol = T::epsilon().sqrt();
+        self.relative_eq(other, tol, tol)
+    }
+}
+
+impl<T: Float> ApproxEq<T> for Complex<T> {
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        (self - other).norm() <= epsilon
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool {
+        let diff = (self - other).norm();
+        diff <= epsilon || diff <= max_relative * self.norm().max(other.norm())
+    }
+}
+
+macro_rules! impl_approx_eq {
+    ($t:ident) => {
+        impl<T: Float> ApproxEq<T> for $t<T> {
+            fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+                self.shape() == other.shape()
+                    && self
+                        .inner
+                        .iter()
+                        .zip(other.inner.iter())
+                        .all(|(a, b)| a.abs_diff_eq(b, epsilon))
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool {
+                self.shape() == other.shape()
+                    && self
+                        .inner
+                        .iter()
+                        .zip(other.inner.iter())
+                        .all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+            }
+        }
+
+        impl<T: Float> $t<T> {
+            /// Whether `self` is within `epsilon` of `e^{i phi} other` in every
+            /// entry, for the phase `phi` that best aligns the two.
+            pub fn equal_up_to_global_phase(&self, other: &Self, epsilon: T) -> bool {
+                if self.shape() != other.shape() {
+                    return false;
+                }
+
+                let overlap = self
+                    .inner
+                    .iter()
+                    .zip(other.inner.iter())
+                    .fold(Complex::zero(), |acc: Complex<T>, (a, b)| {
+                        acc + b.conj() * a
+                    });
+                let phase = if overlap.is_zero() {
+                    Complex::from(T::one())
+                } else {
+                    overlap / overlap.norm()
+                };
+
+                self.inner
+                    .iter()
+                    .zip(other.inner.iter())
+                    .all(|(a, b)| a.abs_diff_eq(&(b * phase), epsilon))
+            }
+        }
+    };
+}
+
+impl_approx_eq!(Ket);
+impl_approx_eq!(Bra);
+impl_approx_eq!(Operator);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_approx_eq() {
+        let a = Complex::new(1e6f64, 0.);
+        assert!(a.relative_eq(&(a + 1e-4), 0., 1e-9));
+        assert!(!a.abs_diff_eq(&(a + 1e-4), 1e-9));
+
+        let h = gates::h::<f64>();
+        assert_ne!(&(&h * &h * &h), &h);
+        assert!((&h * &h * &h).approx_eq(&h));
+        assert!(!h.approx_eq(&gates::x()));
+        assert!(!gates::i::<f64>().approx_eq(&gates::cnot()));
+
+        let ket = Ket::from([0.6f64, 0.8].as_slice());
+        assert!(ket.abs_diff_eq(&Ket::from([0.6 + 1e-10, 0.8].as_slice()), 1e-9));
+    }
+
+    #[test]
+    fn test_global_phase() {
+        use gates::*;
+
+        // `Y = iXZ` and `HZH = X`, each up to a phase.
+        assert!(y::<f64>().equal_up_to_global_phase(&(x() * z()), 1e-12));
+        assert!(!y::<f64>().approx_eq(&(x() * z())));
+        assert!(u1(0.7f64).equal_up_to_global_phase(&rz(0.7), 1e-12));
+
+        let ket = Ket::from([0.6f64, 0.8].as_slice());
+        let rotated = Ket::from([Complex::from_polar(0.6, 2.), Complex::from_polar(0.8, 2.)]);
+        assert!(rotated.equal_up_to_global_phase(&ket, 1e-12));
+        assert!(!rotated.equal_up_to_global_phase(&Ket::from([0.8f64, 0.6].as_slice()), 1e-3));
+        assert!(!ket
+            .dagger()
+            .equal_up_to_global_phase(&Bra::from(&ket.dagger().inner[..1]), 1.));
+    }
+}
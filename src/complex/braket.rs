@@ -11,6 +11,38 @@ pub enum QomputeTypeError {
     NonMatchingSizes,
     #[error("You may have passed in a value with zero size, when a non-zero size was expected")]
     EmptyInput,
+    #[error("A register of qubits needs a power-of-two dimension, but found {0}")]
+    NonQubitDimension(usize),
+    #[error("Expected a Hermitian operator")]
+    NonHermitian,
+    #[error("Expected a positive semidefinite operator")]
+    NonPositive,
+    #[error("Expected an operator with unit trace")]
+    NonUnitTrace,
+    #[error("Expected Kraus operators satisfying sum K^dagger K = I")]
+    NonTracePreserving,
+    #[error("Cannot {op}: expected an operand of shape {expected}, but found {found}")]
+    ShapeMismatch {
+        op: &'static str,
+        expected: Shape,
+        found: Shape,
+    },
+    #[error("Cannot {0}: the result is too large to index")]
+    Overflow(&'static str),
+    #[error("Expected an operator with finite entries")]
+    NonFinite,
+    #[error("An iterative method failed to converge")]
+    NonConvergent,
+    #[error("Expected an invertible operator")]
+    Singular,
+    #[error("Index ({row}, {col}) is out of bounds for shape {shape}")]
+    OutOfBounds { row: usize, col: usize, shape: Shape },
+    #[error("Expected a diagonal operator")]
+    NonDiagonal,
+    #[error("Expected a permutation of basis states")]
+    NonPermutation,
+    #[error("Expected a vector with non-zero norm")]
+    ZeroNorm,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -34,7 +66,7 @@ impl std::ops::Neg for Orientation {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Shape {
     pub(crate) rows: usize,
     pub(crate) cols: usize,
@@ -54,6 +86,12 @@ impl Shape {
     }
 }
 
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
 impl From<(usize, usize)> for Shape {
     fn from(value: (usize, usize)) -> Self {
         Shape {
@@ -151,15 +189,20 @@ impl<T: Float> Operator<T> {
         let n = n.into_iter().collect::<Vec<_>>();
         let shape = (n.len(), n.len()).into();
         let inner = (0..n.len())
-            .map(|i| {
+            .flat_map(|i| {
                 let mut m = vec![Complex::<T>::zero(); n.len()];
                 m[i] = n[i];
                 m.into_iter()
             })
-            .flatten()
             .collect();
         Self { shape, inner }
     }
+
+    pub fn trace(&self) -> Complex<T> {
+        (0..self.rows().min(self.cols()))
+            .map(|i| self[(i, i)])
+            .fold(Complex::<T>::zero(), |a, b| a + b)
+    }
 }
 
 impl<T: Float> ComplexObject<T> for Ket<T> {
@@ -209,16 +252,16 @@ impl<T: Float> ComplexObject<T> for Ket<T> {
             cols: lhs_cols * rhs_cols,
         };
 
-        let mut bra = Ket::new(out_shape.rows);
+        let mut ket = Ket::new(out_shape.rows);
 
-        for i0 in 0..lhs_cols {
-            let i_off = i0 * rhs_cols;
-            for i1 in 0..rhs_cols {
-                bra[i_off + i1] = self[i0] * rhs[i1];
+        for i0 in 0..lhs_rows {
+            let i_off = i0 * rhs_rows;
+            for i1 in 0..rhs_rows {
+                ket[i_off + i1] = self[i0] * rhs[i1];
             }
         }
 
-        bra
+        ket
     }
 }
 
@@ -292,8 +335,8 @@ impl<T: Float> ComplexObject<T> for Operator<T> {
 
     fn dagger(&self) -> Self::ConjugateTranspose {
         let Shape { cols, rows } = self.shape;
-        let inner = (0..rows) // For each row...
-            .zip(std::iter::repeat(0..cols).flatten()) // Traverse width
+        let inner = (0..cols) // For each row of the transpose...
+            .flat_map(|i| (0..rows).map(move |j| (i, j))) // Traverse its width
             .map(|(i, j)| self[(j, i)].conj()) // And grab the opposing conjugate
             .collect::<SmallVec<_>>();
 
@@ -310,7 +353,7 @@ impl<T: Float> ComplexObject<T> for Operator<T> {
     fn hermitian(&self) -> bool {
         self.shape == self.shape.transpose()
             && (0..self.rows())
-                .zip(std::iter::repeat(0..self.cols()).flatten())
+                .flat_map(|i| (0..self.cols()).map(move |j| (i, j)))
                 .all(|(i, j)| self[(i, j)].conj() == self[(j, i)])
     }
 
@@ -531,13 +574,13 @@ impl<T: Float, const M: usize, const N: usize> TryFrom<[[Operator<T>; N]; M]> fo
         };
         let mut op = Operator::new_with_shape(op_shape);
 
-        for i0 in 0..M {
+        for (i0, row) in value.iter().enumerate() {
             let i_off = i0 * inner_shape.rows;
-            for j0 in 0..N {
+            for (j0, block) in row.iter().enumerate() {
                 let j_off = j0 * inner_shape.cols;
                 for i1 in 0..(inner_shape.rows) {
                     for j1 in 0..(inner_shape.cols) {
-                        op[(i_off + i1, j_off + j1)] = value[i0][j0][(i1, j1)];
+                        op[(i_off + i1, j_off + j1)] = block[(i1, j1)];
                     }
                 }
             }
@@ -570,20 +613,31 @@ mod tests {
         let z_proj_op = Operator::from([[cmpx!(18.)]]);
 
         assert_eq!(z_proj, z_proj_op);
+
+        let v = Ket::from([cmpx!(1.), cmpx!(1. j)]);
+        assert_eq!(
+            &v * &v.dagger(),
+            Operator::from([[cmpx!(1.), cmpx!(-1. j)], [cmpx!(1. j), cmpx!(1.)]])
+        );
         assert_eq!(&x * cmpx!(1. - 2. j), Ket::from([cmpx!(5.0)]));
 
+        let a = Ket::from([1., 2.].as_slice());
+        let b = Ket::from([3., 4., 5.].as_slice());
+        assert_eq!(&a & &b, Ket::from([3., 4., 5., 6., 8., 10.].as_slice()));
+        assert_eq!(a.dagger() & b.dagger(), (&a & &b).dagger());
+
         let mut x = Ket::new(5);
         let mut y = Ket::new(5);
 
         (0..5)
             .map(|i| cmpx!(0.5) * i as f32)
             .enumerate()
-            .for_each(|(i, f)| x[i] = f.into());
+            .for_each(|(i, f)| x[i] = f);
 
         (0..5)
             .map(|i| cmpx!(-0.5 j) * i as f32)
             .enumerate()
-            .for_each(|(i, f)| y[i] = f.into());
+            .for_each(|(i, f)| y[i] = f);
 
         assert_eq!(
             y.dagger() * x,
@@ -599,6 +653,14 @@ mod tests {
         let op1 = Operator::from([[cmpx!(1.), cmpx!(1. j)], [cmpx!(-1. j), cmpx!(0.)]]);
 
         assert!(op1.hermitian());
+        assert_eq!(op1.dagger(), op1);
+        assert!(!Operator::from([[0., 1.], [0., 0.]]).hermitian());
+
+        let op4 = Operator::from([[cmpx!(1.), cmpx!(2. j), cmpx!(3.)]]);
+        assert_eq!(
+            op4.dagger(),
+            Operator::from([[cmpx!(1.)], [cmpx!(-2. j)], [cmpx!(3.)]])
+        );
         assert_eq!(op1[(0, 0)], cmpx!(1.));
         assert_eq!(op1[(0, 1)], cmpx!(1. j));
         assert_eq!(op1[(1, 0)], cmpx!(-1. j));
@@ -0,0 +1,128 @@
+use crate::prelude::*;
+
+use super::braket::QomputeTypeError;
+use super::ops::{check_shape, unwrap_or_panic};
+
+/// A diagonal operator, storing only its diagonal. Gates such as `Z`, `CZ`
+/// and `phase(theta)` act on a ket in `O(n)` this way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagonalOperator<T: Float> {
+    diag: Vec<Complex<T>>,
+}
+
+impl<T: Float> DiagonalOperator<T> {
+    pub fn new<I>(diag: I) -> Self
+    where
+        I: IntoIterator<Item = Complex<T>>,
+    {
+        Self {
+            diag: diag.into_iter().collect(),
+        }
+    }
+
+    pub fn diag(&self) -> &[Complex<T>] {
+        &self.diag
+    }
+
+    pub fn dim(&self) -> usize {
+        self.diag.len()
+    }
+
+    pub fn dagger(&self) -> Self {
+        Self::new(self.diag.iter().map(|a| a.conj()))
+    }
+
+    pub fn to_operator(&self) -> Operator<T> {
+        Operator::from_diag(self.diag.iter().copied())
+    }
+
+    pub fn checked_apply(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("apply", (self.dim(), 1).into(), rhs.shape())?;
+        Ok(self
+            .diag
+            .iter()
+            .zip(rhs.inner.iter())
+            .map(|(d, a)| d * a)
+            .to_ket())
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        check_shape(
+            "multiply",
+            (self.dim(), self.dim()).into(),
+            (rhs.dim(), rhs.dim()).into(),
+        )?;
+        Ok(Self::new(
+            self.diag.iter().zip(rhs.diag.iter()).map(|(a, b)| a * b),
+        ))
+    }
+
+    pub fn tensorprod(&self, rhs: &Self) -> Self {
+        Self::new(
+            self.diag
+                .iter()
+                .flat_map(|a| rhs.diag.iter().map(move |b| a * b)),
+        )
+    }
+}
+
+impl<T: Float> From<&DiagonalOperator<T>> for Operator<T> {
+    fn from(op: &DiagonalOperator<T>) -> Self {
+        op.to_operator()
+    }
+}
+
+/// Fails with [`QomputeTypeError::NonDiagonal`] unless every off-diagonal
+/// entry is exactly zero.
+impl<T: Float> TryFrom<&Operator<T>> for DiagonalOperator<T> {
+    type Error = QomputeTypeError;
+
+    fn try_from(op: &Operator<T>) -> Result<Self, Self::Error> {
+        if op.rows() != op.cols() || !op.is_diagonal(T::zero()) {
+            return Err(QomputeTypeError::NonDiagonal);
+        }
+
+        Ok(Self::new((0..op.rows()).map(|i| op[(i, i)])))
+    }
+}
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &DiagonalOperator<T>, rhs: &Ket<T>| -> Ket<T> {
+    unwrap_or_panic(lhs.checked_apply(rhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &DiagonalOperator<T>, rhs: &DiagonalOperator<T>| -> DiagonalOperator<T> {
+    unwrap_or_panic(lhs.checked_mul(rhs))
+});
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &DiagonalOperator<T>, rhs: &DiagonalOperator<T>| -> DiagonalOperator<T> {
+    lhs.tensorprod(rhs)
+});
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &DiagonalOperator<T>, rhs: &Operator<T>| -> Operator<T> {
+    &lhs.to_operator() & rhs
+});
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &Operator<T>, rhs: &DiagonalOperator<T>| -> Operator<T> {
+    lhs & &rhs.to_operator()
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_diagonal() {
+        let z = DiagonalOperator::try_from(&gates::z::<f64>()).unwrap();
+        let s = DiagonalOperator::try_from(&gates::s::<f64>()).unwrap();
+        assert!(DiagonalOperator::try_from(&gates::x::<f64>()).is_err());
+
+        assert_eq!((&s * &s), z);
+        assert_eq!((&s & &z).to_operator(), &gates::s() & &gates::z());
+        assert_eq!(&z & &gates::h::<f64>(), &gates::z() & &gates::h());
+        assert_eq!((&s * &s.dagger()).diag(), [Complex::from(1.); 2]);
+
+        let ket = Ket::from([0.6f64, 0.8].as_slice());
+        assert_eq!(&s * &ket, &gates::s() * &ket);
+        assert!(s.checked_apply(&Ket::from([1f64].as_slice())).is_err());
+    }
+}
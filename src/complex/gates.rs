@@ -1,38 +1,173 @@
 use crate::prelude::*;
 
+use num::One;
 use once_cell::sync::Lazy;
 
 macro_rules! impl_operator {
-    ($x:ident($($v:ident : $ty:ty),+) $($y:tt)+) => {
-        pub fn $x($($v: $ty),+) -> Operator<f32> {
-            Operator::<f32>::from($($y)+)
+    ($x:ident<$t:ident>($($v:ident : $ty:ty),+) $($y:tt)+) => {
+        pub fn $x<$t: Float>($($v: $ty),+) -> Operator<$t> {
+            Operator::<$t>::from($($y)+)
         }
     };
-    ($x:ident $($y:tt)+) => {
-        pub const $x: Lazy<Operator<f32>> = Lazy::new( || {
-            Operator::<f32>::from($($y)+)
-        });
+    ($x:ident, $f:ident, $d:ident diag $($y:tt)+) => {
+        pub fn $d<T: Float>() -> DiagonalOperator<T> {
+            let diag: DiagonalOperator<f64> = $($y)+;
+            DiagonalOperator::new(diag.diag().iter().map(cast_entry))
+        }
+
+        impl_operator!($x, $f $d::<f64>().to_operator());
+    };
+    ($x:ident, $f:ident, $p:ident perm [$($i:expr),+]) => {
+        pub fn $p() -> PermutationOperator {
+            PermutationOperator::new(vec![$($i),+]).unwrap()
+        }
+
+        impl_operator!($x, $f $p().to_operator::<f64>());
+    };
+    ($x:ident, $f:ident $($y:tt)+) => {
+        pub fn $f<T: Float>() -> Operator<T> {
+            cast(&Operator::<f64>::from($($y)+))
+        }
+
+        #[allow(clippy::declare_interior_mutable_const)]
+        pub const $x: Lazy<Operator<f32>> = Lazy::new($f::<f32>);
     };
 }
 
-impl_operator!(I [[1., 0.], [0., 1.]]);
-impl_operator!(ZERO [[0., 0.], [0., 0.]]);
+fn cast_entry<T: Float, U: Float>(c: &Complex<T>) -> Complex<U> {
+    Complex::new(U::from(c.re).unwrap(), U::from(c.im).unwrap())
+}
 
-impl_operator!(H Operator::<f32>::from([[1., 1.], [1., -1.]]) * Complex::from(0.5).sqrt());
-impl_operator!(X [[0., 1.], [1., 0.]]);
-impl_operator!(Y [[cmpx!(0.), cmpx!(-1. j)], [cmpx!(1. j), cmpx!(0.)]]);
-impl_operator!(Z [[1., 0.], [0., -1.]]);
+/// Converts an operator between precisions. The fixed gates are written out
+/// in `f64` and cast down from there.
+pub fn cast<T: Float, U: Float>(op: &Operator<T>) -> Operator<U> {
+    Operator {
+        shape: op.shape(),
+        inner: op.inner.iter().map(cast_entry).collect(),
+    }
+}
+
+// Gates that are diagonal or permute basis states also come in that form, as
+// `z_diag` or `x_perm`, and their dense matrices are built from it.
+impl_operator!(I, i, i_diag diag DiagonalOperator::new([1., 1.].map(Complex::from)));
+impl_operator!(ZERO, zero [[0., 0.], [0., 0.]]);
+
+impl_operator!(H, h Operator::<f64>::from([[1., 1.], [1., -1.]]) * Complex::from(0.5).sqrt());
+impl_operator!(X, x, x_perm perm [1, 0]);
+impl_operator!(Y, y [[cmpx!(0.), cmpx!(-1. j)], [cmpx!(1. j), cmpx!(0.)]]);
+impl_operator!(Z, z, z_diag diag DiagonalOperator::new([1., -1.].map(Complex::from)));
+
+impl_operator!(S, s, s_diag diag DiagonalOperator::new([cmpx!(1.), cmpx!(1. j)]));
+impl_operator!(SDG, sdg, sdg_diag diag DiagonalOperator::new([cmpx!(1.), cmpx!(-1. j)]));
+impl_operator!(T, t, t_diag diag phase_diag(std::f64::consts::FRAC_PI_4));
+impl_operator!(TDG, tdg, tdg_diag diag phase_diag(-std::f64::consts::FRAC_PI_4));
+
+impl_operator!(CNOT, cnot [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 0., 1.], [0., 0., 1., 0.]]);
+impl_operator!(CZ, cz Operator::from_diag([1.,1.,1.,-1.].into_iter().map(Complex::from)));
+
+impl_operator!(SWAP, swap, swap_perm perm [0, 2, 1, 3]);
+
+impl_operator!(TOFFOLI, toffoli controlled(&x(), 2));
+impl_operator!(CCZ, ccz controlled(&z(), 2));
+
+/// Extends `gate` with `n_controls` leading control qubits, giving the block
+/// diagonal matrix `diag(I, .., I, gate)`.
+pub fn controlled<T: Float>(gate: &Operator<T>, n_controls: usize) -> Operator<T> {
+    let (rows, cols) = gate.shape().into();
+    let offset = ((1 << n_controls) - 1) * rows;
+    let mut out = Operator::new_with_shape((offset + rows, offset + cols).into());
+
+    for i in 0..rows {
+        for j in 0..cols {
+            out[(offset + i, offset + j)] = gate[(i, j)];
+        }
+    }
 
-impl_operator!(CNOT [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 0., 1.], [0., 0., 1., 0.]]);
-impl_operator!(CZ Operator::from_diag([1.,1.,1.,-1.].into_iter().map(Complex::from)));
+    out
+}
+
+/// A gate matrix in the cheapest form that holds it exactly, so that diagonal
+/// and permutation gates are applied to a state in `O(n)` rather than `O(n^2)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GateMatrix<T: Float> {
+    Diagonal(DiagonalOperator<T>),
+    Permutation(PermutationOperator),
+    Dense(Operator<T>),
+}
+
+impl<T: Float> GateMatrix<T> {
+    pub fn dim(&self) -> usize {
+        match self {
+            GateMatrix::Diagonal(d) => d.dim(),
+            GateMatrix::Permutation(p) => p.dim(),
+            GateMatrix::Dense(op) => op.rows(),
+        }
+    }
+
+    pub fn to_operator(&self) -> Operator<T> {
+        match self {
+            GateMatrix::Diagonal(d) => d.to_operator(),
+            GateMatrix::Permutation(p) => p.to_operator(),
+            GateMatrix::Dense(op) => op.clone(),
+        }
+    }
+}
+
+/// Picks the diagonal form, then the permutation form, falling back to the
+/// dense matrix. This scans every entry of `op`, so circuits build the form
+/// of built-in gates directly with `Instruction::gate_matrix` instead.
+impl<T: Float> From<Operator<T>> for GateMatrix<T> {
+    fn from(op: Operator<T>) -> Self {
+        if let Ok(d) = DiagonalOperator::try_from(&op) {
+            GateMatrix::Diagonal(d)
+        } else if let Ok(p) = PermutationOperator::try_from(&op) {
+            GateMatrix::Permutation(p)
+        } else {
+            GateMatrix::Dense(op)
+        }
+    }
+}
+
+pub fn phase_diag<T: Float>(theta: T) -> DiagonalOperator<T> {
+    DiagonalOperator::new([Complex::one(), Complex::from_polar(T::one(), theta)])
+}
+
+impl_operator!(phase<T>(theta: T) phase_diag(theta).to_operator());
+
+// Rotations `exp(-i theta P / 2)` about the Pauli axis `P`.
+impl_operator!(rx<T>(theta: T) {
+    let (s, c) = (theta / T::from(2).unwrap()).sin_cos();
+    let s = Complex::new(T::zero(), -s);
+    [[c.into(), s], [s, c.into()]]
+});
+impl_operator!(ry<T>(theta: T) {
+    let (s, c) = (theta / T::from(2).unwrap()).sin_cos();
+    [[c, -s], [s, c]]
+});
+impl_operator!(rz<T>(theta: T) rz_diag(theta).to_operator());
 
-impl_operator!(SWAP [[1., 0., 0., 0.], [0., 0., 1., 0.], [0., 1., 0., 0.], [0., 0., 0., 1.]]);
+pub fn rz_diag<T: Float>(theta: T) -> DiagonalOperator<T> {
+    let half = theta / T::from(2).unwrap();
+    DiagonalOperator::new([Complex::from_polar(T::one(), -half), Complex::from_polar(T::one(), half)])
+}
 
-impl_operator!(phase(theta: f32) {
-    [[ cmpx!(1.), cmpx!(0.)], [cmpx!(0.), Complex::new(0., theta).exp()]]
+// The general single-qubit gate, equal to
+// `[[cos(theta/2), -e^{i lambda} sin(theta/2)],
+//   [e^{i phi} sin(theta/2), e^{i (phi + lambda)} cos(theta/2)]]`.
+impl_operator!(u<T>(theta: T, phi: T, lambda: T) {
+    let (s, c) = (theta / T::from(2).unwrap()).sin_cos();
+    let phase = |x: T| Complex::from_polar(T::one(), x);
+    [[c.into(), -phase(lambda) * s], [phase(phi) * s, phase(phi + lambda) * c]]
 });
 
+impl_operator!(cphase<T>(theta: T) controlled(&phase(theta), 1));
+
+impl_operator!(u1<T>(lambda: T) phase(lambda));
+impl_operator!(u2<T>(phi: T, lambda: T) u(T::from(std::f64::consts::FRAC_PI_2).unwrap(), phi, lambda));
+impl_operator!(u3<T>(theta: T, phi: T, lambda: T) u(theta, phi, lambda));
+
 #[cfg(test)]
+#[allow(clippy::borrow_interior_mutable_const)]
 mod tests {
     use crate::prelude::*;
 
@@ -50,25 +185,21 @@ mod tests {
             Operator::from_diag([1., 1., 1., 1.].into_iter().map(Complex::from))
         );
         
-        assert_eq!(
-            &*H & &*I,
-            Operator::from([[1., 0., 1., 0.], [0., 1., 0., 1.], [1., 0., -1., 0.], [0., 1., 0., -1.]]) * cmpx!(0.5).sqrt(),
-        );
+        assert!((&*H & &*I).approx_eq(
+            &(Operator::from([[1., 0., 1., 0.], [0., 1., 0., 1.], [1., 0., -1., 0.], [0., 1., 0., -1.]]) * cmpx!(0.5).sqrt()),
+        ));
         
-        assert_eq!(
-            &*I & &*H,
-            Operator::from([[1., 1., 0., 0.], [1., -1., 0., 0.], [0., 0., 1., 1.], [0., 0., 1., -1.]]) * cmpx!(0.5).sqrt(),
-        );
+        assert!((&*I & &*H).approx_eq(
+            &(Operator::from([[1., 1., 0., 0.], [1., -1., 0., 0.], [0., 0., 1., 1.], [0., 0., 1., -1.]]) * cmpx!(0.5).sqrt()),
+        ));
 
-        assert_eq!(
-            &*H & &*I,
-            Operator::try_from([[I.clone(), I.clone()],[I.clone(), -I.clone()]]).unwrap() * cmpx!(0.5).sqrt(),
-        );
+        assert!((&*H & &*I).approx_eq(
+            &(Operator::try_from([[I.clone(), I.clone()],[I.clone(), -I.clone()]]).unwrap() * cmpx!(0.5).sqrt()),
+        ));
         
-        assert_eq!(
-            &*I & &*H,
-            Operator::from([[1., 1., 0., 0.], [1., -1., 0., 0.], [0., 0., 1., 1.], [0., 0., 1., -1.]]) * cmpx!(0.5).sqrt(),
-        );
+        assert!((&*I & &*H).approx_eq(
+            &(Operator::from([[1., 1., 0., 0.], [1., -1., 0., 0.], [0., 0., 1., 1.], [0., 0., 1., -1.]]) * cmpx!(0.5).sqrt()),
+        ));
 
         
         assert_eq!(
@@ -76,4 +207,66 @@ mod tests {
             Operator::try_from([[H.clone(), ZERO.clone()], [ZERO.clone(), H.clone()]]).unwrap(),
         );
     }
+
+    #[test]
+    fn test_rotations() {
+        use gates::*;
+
+        let pi = std::f64::consts::PI;
+
+        assert!((rx(0.3) * rx(0.4)).approx_eq(&rx(0.7)));
+        assert!((ry(-1.2) * ry(0.2)).approx_eq(&ry(-1.)));
+        assert!((rz(0.5) * rz(2.)).approx_eq(&rz(2.5)));
+
+        let h = Operator::from([[1., 1.], [1., -1.]]) * Complex::from(0.5f64.sqrt());
+        assert!(u2(0., pi).approx_eq(&h));
+        assert!(u3(pi, 0., pi).approx_eq(&Operator::from([[0., 1.], [1., 0.]])));
+        assert!(u1(0.9).approx_eq(&(rz(0.9) * Complex::from_polar(1., 0.45))));
+        assert!(u(0.2, 0.5, -0.1).approx_eq(&(rz(0.5) * ry(0.2) * rz(-0.1) * Complex::from_polar(1., 0.2))));
+        assert_eq!(&u1(pi as f32), &phase(pi as f32));
+    }
+
+    #[test]
+    fn test_precision() {
+        use gates::*;
+
+        let h = h::<f64>();
+        assert_eq!(h[(0, 0)], Complex::from(0.5f64.sqrt()));
+        assert_eq!(&cast::<f64, f32>(&h), &*H);
+
+        let tt = t::<f64>() * t();
+        assert!(tt.abs_diff_eq(&s(), 1e-15));
+        assert_eq!(&(s::<f64>() * sdg()), &i());
+        assert_eq!(&(cnot::<f64>() * (&x() & &i()) * cnot()), &(&x() & &x()));
+        assert_eq!(&*TOFFOLI, &controlled(&cnot(), 1));
+    }
+
+    #[test]
+    fn test_unitary() {
+        use gates::*;
+
+        for gate in [&*H, &*X, &*Y, &*Z, &*S, &*SDG, &*T, &*TDG, &*CNOT, &*CZ, &*SWAP, &*TOFFOLI, &*CCZ] {
+            assert!(gate.is_unitary(1e-6));
+        }
+        assert!(u(0.3f64, -1.2, 2.5).is_unitary(1e-12));
+        assert!(!ZERO.is_unitary(1e-6));
+    }
+
+    #[test]
+    fn test_gate_matrix() {
+        use gates::*;
+
+        for op in [z::<f64>(), s(), t(), cz(), ccz(), rz(0.4), cphase(1.2), i()] {
+            assert!(matches!(GateMatrix::from(op), GateMatrix::Diagonal(_)));
+        }
+        for op in [x::<f64>(), cnot(), swap(), toffoli()] {
+            assert!(matches!(GateMatrix::from(op), GateMatrix::Permutation(_)));
+        }
+        for op in [h::<f64>(), y(), rx(0.3), u(0.1, 0.2, 0.3)] {
+            assert!(matches!(GateMatrix::from(op.clone()), GateMatrix::Dense(ref d) if *d == op));
+        }
+
+        assert_eq!(GateMatrix::from(swap::<f64>()).to_operator(), swap());
+        assert_eq!(GateMatrix::from(ccz::<f64>()).dim(), 8);
+    }
 }
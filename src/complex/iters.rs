@@ -32,7 +32,7 @@ impl<T: Float, const N: usize> ToOperator<T> for &[[T; N]] {
         let rows = self.len();
         Operator {
             shape: Shape { rows, cols: N },
-            inner: self.flat().into_iter().map(Complex::<T>::from).collect(),
+            inner: self.flat().iter().map(Complex::<T>::from).collect(),
         }
     }
 }
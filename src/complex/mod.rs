@@ -1,9 +1,15 @@
 pub use num::{Float, complex::Complex};
 
+pub mod approx;
 pub mod braket;
+pub mod diagonal;
 pub mod ops;
 pub mod gates;
+pub mod permutation;
+pub mod sparse;
+pub mod view;
 mod iters;
+mod kernels;
 
 pub use iters::{ToBra, ToKet, ToOperator};
 
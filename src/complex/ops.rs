@@ -2,92 +2,253 @@ use num::{Zero, One};
 
 use crate::prelude::*;
 
-use super::braket::{Bra, Ket, Operator};
+use smallvec::smallvec;
 
-auto_ops::impl_op_ex!(+ <T: Float> |lhs: &Ket<T>, rhs: &Ket<T>| -> Ket<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Ket {
-        inner: lhs
+use super::braket::{Bra, Ket, Operator, QomputeTypeError};
+use super::kernels;
+
+pub(crate) fn check_shape(op: &'static str, expected: Shape, found: Shape) -> Result<(), QomputeTypeError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(QomputeTypeError::ShapeMismatch { op, expected, found })
+    }
+}
+
+pub(crate) fn unwrap_or_panic<R>(res: Result<R, QomputeTypeError>) -> R {
+    res.unwrap_or_else(|err| panic!("{}", err))
+}
+
+macro_rules! impl_checked {
+    ($t:ident $(, $field:ident)?) => {
+        impl<T: Float> $t<T> {
+            pub fn checked_add(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+                check_shape("add", self.shape(), rhs.shape())?;
+                Ok(Self {
+                    $($field: self.$field,)?
+                    inner: self.inner.iter().zip(rhs.inner.iter()).map(|(a, b)| *a + *b).collect(),
+                })
+            }
+
+            pub fn checked_sub(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+                check_shape("subtract", self.shape(), rhs.shape())?;
+                Ok(Self {
+                    $($field: self.$field,)?
+                    inner: self.inner.iter().zip(rhs.inner.iter()).map(|(a, b)| *a - *b).collect(),
+                })
+            }
+
+            /// Fails if the number of entries in the product overflows a `usize`.
+            pub fn checked_tensorprod(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+                self.size()
+                    .checked_mul(rhs.size())
+                    .ok_or(QomputeTypeError::Overflow("take the tensor product"))?;
+                Ok(self.tensorprod(rhs))
+            }
+        }
+    };
+}
+
+impl_checked!(Ket);
+impl_checked!(Bra);
+impl_checked!(Operator, shape);
+
+impl<T: Float> Bra<T> {
+    pub fn checked_inner(&self, rhs: &Ket<T>) -> Result<Complex<T>, QomputeTypeError> {
+        check_shape("take the inner product", (self.cols(), 1).into(), rhs.shape())?;
+        Ok(self
             .inner
             .iter()
             .zip(rhs.inner.iter())
-            .map(|(a, b)| *a + *b)
-            .collect(),
+            .map(|(a, b)| *a * *b)
+            .fold(Complex::<T>::zero(), |a, b| a + b))
+    }
+
+    /// `<b| A`, a row vector times a matrix.
+    pub fn checked_mul(&self, rhs: &Operator<T>) -> Result<Self, QomputeTypeError> {
+        let (rhs_rows, rhs_cols) = rhs.shape().into();
+        check_shape("multiply", (self.cols(), rhs_cols).into(), rhs.shape())?;
+
+        let mut bra = Bra {
+            inner: smallvec![Complex::zero(); rhs_cols],
+        };
+        kernels::matmul(&self.inner, &rhs.inner, &mut bra.inner, (1, rhs_rows, rhs_cols));
+
+        Ok(bra)
+    }
+}
+
+impl<T: Float> Operator<T> {
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        let (lhs_rows, lhs_cols) = self.shape().into();
+        let (_, rhs_cols) = rhs.shape().into();
+
+        check_shape("multiply", (lhs_cols, rhs_cols).into(), rhs.shape())?;
+
+        let mut op = Operator {
+            shape: (lhs_rows, rhs_cols).into(),
+            inner: smallvec![Complex::zero(); lhs_rows * rhs_cols],
+        };
+        kernels::matmul(&self.inner, &rhs.inner, &mut op.inner, (lhs_rows, lhs_cols, rhs_cols));
+
+        Ok(op)
+    }
+
+    pub fn checked_apply(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        let (rows, cols) = self.shape().into();
+        check_shape("apply", (cols, 1).into(), rhs.shape())?;
+
+        let mut ket = Ket {
+            inner: smallvec![Complex::zero(); rows],
+        };
+        kernels::matvec(&self.inner, &rhs.inner, &mut ket.inner, (rows, cols));
+
+        Ok(ket)
+    }
+
+    /// Writes `self * src` into `dst`, reusing its buffer. This is the
+    /// allocation-free way to apply an operator: once `dst` has capacity for
+    /// `self.rows()` entries nothing is allocated, so alternating two kets as
+    /// `src` and `dst` keeps a loop of applications off the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` does not have `self.cols()` entries.
+    pub fn apply_into(&self, src: &Ket<T>, dst: &mut Ket<T>) {
+        let (rows, cols) = self.shape().into();
+        unwrap_or_panic(check_shape("apply", (cols, 1).into(), src.shape()));
+
+        dst.inner.resize(rows, Complex::zero());
+        kernels::matvec(&self.inner, &src.inner, &mut dst.inner, (rows, cols));
+    }
+
+    /// Replaces `ket` with `self * ket`, using `scratch` as the output buffer
+    /// and then swapping the two.
+    ///
+    /// Once `scratch` has grown to `self.rows()` entries, repeated calls only
+    /// trade the two buffers back and forth and never allocate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ket` does not have `self.cols()` entries.
+    pub fn apply_to_with(&self, ket: &mut Ket<T>, scratch: &mut Ket<T>) {
+        self.apply_into(ket, scratch);
+        std::mem::swap(ket, scratch);
+    }
+
+    /// Replaces `ket` with `self * ket`.
+    ///
+    /// A dense product cannot overwrite its own input, so this allocates a new
+    /// buffer for more than two amplitudes. Use [`Operator::apply_to_with`] to
+    /// reuse one across calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ket` does not have `self.cols()` entries.
+    pub fn apply_to(&self, ket: &mut Ket<T>) {
+        self.apply_to_with(ket, &mut Ket { inner: smallvec![] });
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Float + Send + Sync> Operator<T> {
+    /// Like [`Operator::checked_mul`], but splits large products across the
+    /// rayon thread pool.
+    pub fn par_checked_mul(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        let (lhs_rows, lhs_cols) = self.shape().into();
+        let (_, rhs_cols) = rhs.shape().into();
+
+        check_shape("multiply", (lhs_cols, rhs_cols).into(), rhs.shape())?;
+
+        let mut op = Operator {
+            shape: (lhs_rows, rhs_cols).into(),
+            inner: smallvec![Complex::zero(); lhs_rows * rhs_cols],
+        };
+        kernels::par_matmul(
+            &self.inner,
+            &rhs.inner,
+            &mut op.inner,
+            (lhs_rows, lhs_cols, rhs_cols),
+        );
+
+        Ok(op)
+    }
+
+    /// Like [`Operator::checked_apply`], but splits large products across the
+    /// rayon thread pool.
+    pub fn par_checked_apply(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        let (rows, cols) = self.shape().into();
+        check_shape("apply", (cols, 1).into(), rhs.shape())?;
+
+        let mut ket = Ket {
+            inner: smallvec![Complex::zero(); rows],
+        };
+        kernels::par_matvec(&self.inner, &rhs.inner, &mut ket.inner, (rows, cols));
+
+        Ok(ket)
     }
+}
+
+macro_rules! impl_assign {
+    ($t:ident) => {
+        auto_ops::impl_op_ex!(+= <T: Float> |lhs: &mut $t<T>, rhs: &$t<T>| {
+            unwrap_or_panic(check_shape("add", lhs.shape(), rhs.shape()));
+            lhs.inner.iter_mut().zip(rhs.inner.iter()).for_each(|(a, b)| *a = *a + *b);
+        });
+
+        auto_ops::impl_op_ex!(-= <T: Float> |lhs: &mut $t<T>, rhs: &$t<T>| {
+            unwrap_or_panic(check_shape("subtract", lhs.shape(), rhs.shape()));
+            lhs.inner.iter_mut().zip(rhs.inner.iter()).for_each(|(a, b)| *a = *a - *b);
+        });
+
+        auto_ops::impl_op_ex!(*= <T: Float> |lhs: &mut $t<T>, rhs: Complex<T>| {
+            lhs.inner.iter_mut().for_each(|a| *a = *a * rhs);
+        });
+    };
+}
+
+impl_assign!(Ket);
+impl_assign!(Bra);
+impl_assign!(Operator);
+
+// Allocates on every use, see `Operator::apply_to_with` for hot loops.
+auto_ops::impl_op_ex!(*= <T: Float> |lhs: &mut Ket<T>, rhs: &Operator<T>| {
+    rhs.apply_to(lhs)
+});
+
+auto_ops::impl_op_ex!(+ <T: Float> |lhs: &Ket<T>, rhs: &Ket<T>| -> Ket<T> {
+    unwrap_or_panic(lhs.checked_add(rhs))
 });
 
 auto_ops::impl_op_ex!(+ <T: Float> |lhs: &Bra<T>, rhs: &Bra<T>| -> Bra<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Bra {
-        inner: lhs
-            .inner
-            .iter()
-            .zip(rhs.inner.iter())
-            .map(|(a, b)| *a + *b)
-            .collect(),
-    }
+    unwrap_or_panic(lhs.checked_add(rhs))
 });
 
 auto_ops::impl_op_ex!(+ <T: Float> |lhs: &Operator<T>, rhs: &Operator<T>| -> Operator<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Operator {
-        shape: lhs.shape,
-        inner: lhs
-            .inner
-            .iter()
-            .zip(rhs.inner.iter())
-            .map(|(a, b)| *a + *b)
-            .collect(),
-    }
+    unwrap_or_panic(lhs.checked_add(rhs))
 });
 
 auto_ops::impl_op_ex!(- <T: Float> |lhs: &Ket<T>, rhs: &Ket<T>| -> Ket<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Ket {
-        inner: lhs
-            .inner
-            .iter()
-            .zip(rhs.inner.iter())
-            .map(|(a, b)| *a - *b)
-            .collect(),
-    }
+    unwrap_or_panic(lhs.checked_sub(rhs))
 });
 
 auto_ops::impl_op_ex!(- <T: Float> |lhs: &Bra<T>, rhs: &Bra<T>| -> Bra<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Bra {
-        inner: lhs
-            .inner
-            .iter()
-            .zip(rhs.inner.iter())
-            .map(|(a, b)| *a - *b)
-            .collect(),
-    }
+    unwrap_or_panic(lhs.checked_sub(rhs))
 });
 
 auto_ops::impl_op_ex!(- <T: Float> |lhs: &Operator<T>, rhs: &Operator<T>| -> Operator<T> {
-    assert_eq!(lhs.shape(), rhs.shape());
-    Operator {
-        shape: lhs.shape,
-        inner: lhs
-            .inner
-            .iter()
-            .zip(rhs.inner.iter())
-            .map(|(a, b)| *a - *b)
-            .collect(),
-    }
+    unwrap_or_panic(lhs.checked_sub(rhs))
 });
 
 auto_ops::impl_op_ex!(* <T: Float> |lhs: &Bra<T>, rhs: &Ket<T>| -> Complex<T> {
-    assert_eq!(lhs.shape().cols, rhs.shape().rows);
-    lhs.inner.iter().zip(rhs.inner.iter()).map(|(a, b)| *a * *b).fold(Complex::<T>::zero(), |a, b| { a + b })
+    unwrap_or_panic(lhs.checked_inner(rhs))
 });
 
 auto_ops::impl_op_ex!(* <T: Float> |lhs: &Ket<T>, rhs: &Bra<T>| -> Operator<T> {
-    let (_, lhs_rows) = lhs.shape().into();
-    let (rhs_cols, _) = rhs.shape().into();
+    let (lhs_rows, _) = lhs.shape().into();
+    let (_, rhs_cols) = rhs.shape().into();
 
-    let mut op = Operator::new_with_shape((rhs.rows(), lhs.cols()).into());
+    let mut op = Operator::new_with_shape((lhs_rows, rhs_cols).into());
 
     for row in 0..lhs_rows {
         for col in 0..rhs_cols {
@@ -99,21 +260,15 @@ auto_ops::impl_op_ex!(* <T: Float> |lhs: &Ket<T>, rhs: &Bra<T>| -> Operator<T> {
 });
 
 auto_ops::impl_op_ex!(* <T: Float> |lhs: &Operator<T>, rhs: &Operator<T>| -> Operator<T> {
-    let (lhs_rows, lhs_cols) = lhs.shape().into();
-    let (rhs_rows, rhs_cols) = rhs.shape().into();
-
-    assert_eq!(lhs_cols, rhs_rows);
-    let ks = lhs_cols;
-
-    let mut op = Operator::new_with_shape((lhs_cols, rhs_rows).into());
+    unwrap_or_panic(lhs.checked_mul(rhs))
+});
 
-    for row in 0..lhs_rows {
-        for col in 0..rhs_cols {
-                op[(row, col)] = (0..ks).map(|k| lhs[(row, k)] * rhs[(k, col)]).sum();
-        }
-    }
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &Operator<T>, rhs: &Ket<T>| -> Ket<T> {
+    unwrap_or_panic(lhs.checked_apply(rhs))
+});
 
-    op
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &Bra<T>, rhs: &Operator<T>| -> Bra<T> {
+    unwrap_or_panic(lhs.checked_mul(rhs))
 });
 
 auto_ops::impl_op_ex!(* <T: Float> |lhs: &Ket<T>, rhs: Complex<T>| -> Ket<T> {
@@ -141,15 +296,15 @@ auto_ops::impl_op_ex!(* <T: Float> |rhs: Complex<T>, lhs: &Operator<T>| -> Opera
 });
 
 auto_ops::impl_op_ex!(& <T: Float> |lhs: &Ket<T>, rhs: &Ket<T>| -> Ket<T> {
-    lhs.tensorprod(rhs)
+    unwrap_or_panic(lhs.checked_tensorprod(rhs))
 });
 
 auto_ops::impl_op_ex!(& <T: Float> |lhs: &Bra<T>, rhs: &Bra<T>| -> Bra<T> {
-    lhs.tensorprod(rhs)
+    unwrap_or_panic(lhs.checked_tensorprod(rhs))
 });
 
 auto_ops::impl_op_ex!(& <T: Float> |lhs: &Operator<T>, rhs: &Operator<T>| -> Operator<T> {
-    lhs.tensorprod(rhs)
+    unwrap_or_panic(lhs.checked_tensorprod(rhs))
 });
 
 auto_ops::impl_op_ex!(- <T: Float> |lhs: &Ket<T>| -> Ket<T> {
@@ -163,3 +318,116 @@ auto_ops::impl_op_ex!(- <T: Float> |lhs: &Bra<T>| -> Bra<T> {
 auto_ops::impl_op_ex!(- <T: Float> |lhs: &Operator<T>| -> Operator<T> {
     lhs * (-Complex::<T>::one())
 });
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_checked() {
+        let ket = Ket::from([1f64, 2.].as_slice());
+        let bra = Bra::from([1f64, 2., 3.].as_slice());
+
+        assert_eq!(
+            ket.checked_add(&Ket::from([1f64].as_slice())),
+            Err(QomputeTypeError::ShapeMismatch {
+                op: "add",
+                expected: (2, 1).into(),
+                found: (1, 1).into(),
+            })
+        );
+        assert_eq!(ket.checked_sub(&ket), Ok(Ket::from([0f64, 0.].as_slice())));
+        assert_eq!(
+            bra.checked_inner(&ket).unwrap_err().to_string(),
+            "Cannot take the inner product: expected an operand of shape 3x1, but found 2x1"
+        );
+        assert_eq!(ket.dagger().checked_inner(&ket), Ok(Complex::from(5.)));
+
+        let wide = Operator::from([[1f64, 2., 3.], [4., 5., 6.]]);
+        let tall = Operator::from([[1f64], [0.], [-1.]]);
+        assert_eq!(wide.checked_mul(&tall), Ok(Operator::from([[-2f64], [-2.]])));
+        assert!(matches!(
+            tall.checked_mul(&wide),
+            Err(QomputeTypeError::ShapeMismatch { op: "multiply", .. })
+        ));
+        assert_eq!(&wide * &tall, Operator::from([[-2f64], [-2.]]));
+
+        let huge = Operator::<f64> {
+            shape: (1 << 20, 1 << 20).into(),
+            inner: Default::default(),
+        };
+        assert_eq!(
+            huge.checked_tensorprod(&huge),
+            Err(QomputeTypeError::Overflow("take the tensor product"))
+        );
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut ket = Ket::from([1f64, 2.].as_slice());
+        ket += &Ket::from([1f64, 1.].as_slice());
+        ket -= Ket::from([0f64, 2.].as_slice());
+        ket *= Complex::from(2.);
+        assert_eq!(ket, Ket::from([4f64, 2.].as_slice()));
+
+        let x = gates::x::<f64>();
+        ket *= &x;
+        assert_eq!(ket, Ket::from([2f64, 4.].as_slice()));
+        ket *= x;
+        assert_eq!(ket, Ket::from([4f64, 2.].as_slice()));
+
+        let mut out = Ket::from([0f64; 4].as_slice());
+        let ptr = out.inner.as_ptr();
+        Operator::from([[1f64, 1.], [1., -1.]]).apply_into(&ket, &mut out);
+        assert_eq!(out, Ket::from([6f64, 2.].as_slice()));
+        assert_eq!(out.inner.as_ptr(), ptr);
+
+        let mut ket = Ket::from([1f64, 0., 0., 0.].as_slice());
+        let mut scratch = Ket::from([0f64; 4].as_slice());
+        let ptrs = [ket.inner.as_ptr(), scratch.inner.as_ptr()];
+        let cnot = gates::cnot::<f64>();
+        for _ in 0..4 {
+            cnot.apply_to_with(&mut ket, &mut scratch);
+            assert_eq!(ket.inner.as_ptr(), ptrs[1]);
+            cnot.apply_to_with(&mut ket, &mut scratch);
+            assert_eq!(ket.inner.as_ptr(), ptrs[0]);
+        }
+        assert_eq!(scratch.inner.as_ptr(), ptrs[1]);
+        assert_eq!(ket, Ket::from([1f64, 0., 0., 0.].as_slice()));
+
+        let mut op = Operator::from([[1f64, 2.], [3., 4.]]);
+        op -= &Operator::from([[1f64, 0.], [0., 1.]]);
+        op *= Complex::from(-1.);
+        assert_eq!(op, Operator::from([[0f64, -2.], [-3., -3.]]));
+    }
+
+    #[test]
+    fn test_apply() {
+        let ket = Ket::from([1f64, 2.].as_slice());
+        let tall = Operator::from([[1f64, 2.], [3., 4.], [5., 6.]]);
+        let wide = Operator::from([[1f64, 3., 5.], [2., 4., 6.]]);
+
+        assert_eq!(&tall * &ket, Ket::from([5f64, 11., 17.].as_slice()));
+        assert_eq!(&ket.dagger() * &wide, Bra::from([5f64, 11., 17.].as_slice()));
+        assert_eq!(gates::x::<f64>() * ket.clone(), Ket::from([2f64, 1.].as_slice()));
+
+        // <psi|U^dagger U|psi> = <psi|psi>
+        let psi = &gates::h::<f64>() * &ket;
+        assert!((&psi.dagger() * &psi).approx_eq(&Complex::from(5.)));
+        assert!((&psi.dagger() * &gates::h() * &ket).approx_eq(&Complex::from(5.)));
+
+        assert_eq!(
+            wide.checked_apply(&ket).unwrap_err().to_string(),
+            "Cannot apply: expected an operand of shape 3x1, but found 2x1"
+        );
+        assert!(ket.dagger().checked_mul(&tall).is_err());
+
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(tall.par_checked_apply(&ket).unwrap(), &tall * &ket);
+            assert_eq!(wide.par_checked_mul(&tall).unwrap(), &wide * &tall);
+            assert!(wide.par_checked_apply(&ket).is_err());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::prelude::*;
+
+use num::{One, Zero};
+
+use super::braket::QomputeTypeError;
+use super::ops::{check_shape, unwrap_or_panic};
+
+/// A permutation of basis states, mapping `|j>` to `|perm[j]>`. Gates such as
+/// `X`, `CNOT` and `SWAP` act on a ket in `O(n)` this way.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PermutationOperator {
+    perm: Vec<usize>,
+}
+
+impl PermutationOperator {
+    /// Fails with [`QomputeTypeError::NonPermutation`] unless `perm` contains
+    /// each of `0..perm.len()` exactly once.
+    pub fn new(perm: Vec<usize>) -> Result<Self, QomputeTypeError> {
+        let mut seen = vec![false; perm.len()];
+
+        for &i in &perm {
+            if i >= perm.len() || seen[i] {
+                return Err(QomputeTypeError::NonPermutation);
+            }
+            seen[i] = true;
+        }
+
+        Ok(Self { perm })
+    }
+
+    pub fn identity(n: usize) -> Self {
+        Self {
+            perm: (0..n).collect(),
+        }
+    }
+
+    pub fn perm(&self) -> &[usize] {
+        &self.perm
+    }
+
+    pub fn dim(&self) -> usize {
+        self.perm.len()
+    }
+
+    /// The inverse permutation, which is also the conjugate transpose.
+    pub fn inverse(&self) -> Self {
+        let mut perm = vec![0; self.dim()];
+        self.perm.iter().enumerate().for_each(|(j, &i)| perm[i] = j);
+        Self { perm }
+    }
+
+    pub fn to_operator<T: Float>(&self) -> Operator<T> {
+        let n = self.dim();
+        let mut op = Operator::new_with_shape((n, n).into());
+        op.inner.iter_mut().for_each(|a| *a = Complex::zero());
+        self.perm
+            .iter()
+            .enumerate()
+            .for_each(|(j, &i)| op[(i, j)] = Complex::one());
+        op
+    }
+
+    pub fn checked_apply<T: Float>(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("apply", (self.dim(), 1).into(), rhs.shape())?;
+
+        let mut out = rhs.clone();
+        self.perm
+            .iter()
+            .zip(rhs.inner.iter())
+            .for_each(|(&i, a)| out.inner[i] = *a);
+        Ok(out)
+    }
+
+    /// The composition `self * rhs`, applying `rhs` first.
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        check_shape(
+            "multiply",
+            (self.dim(), self.dim()).into(),
+            (rhs.dim(), rhs.dim()).into(),
+        )?;
+        Ok(Self {
+            perm: rhs.perm.iter().map(|&j| self.perm[j]).collect(),
+        })
+    }
+
+    pub fn tensorprod(&self, rhs: &Self) -> Self {
+        let n = rhs.dim();
+        Self {
+            perm: self
+                .perm
+                .iter()
+                .flat_map(|&i| rhs.perm.iter().map(move |&j| i * n + j))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Float> From<&PermutationOperator> for Operator<T> {
+    fn from(op: &PermutationOperator) -> Self {
+        op.to_operator()
+    }
+}
+
+/// Fails with [`QomputeTypeError::NonPermutation`] unless every column holds
+/// a single entry, exactly one, in distinct rows.
+impl<T: Float> TryFrom<&Operator<T>> for PermutationOperator {
+    type Error = QomputeTypeError;
+
+    fn try_from(op: &Operator<T>) -> Result<Self, Self::Error> {
+        if op.rows() != op.cols() {
+            return Err(QomputeTypeError::NonPermutation);
+        }
+
+        let perm = (0..op.cols())
+            .map(|j| {
+                let mut rows = (0..op.rows()).filter(|&i| !op[(i, j)].is_zero());
+                match (rows.next(), rows.next()) {
+                    (Some(i), None) if op[(i, j)].is_one() => Ok(i),
+                    _ => Err(QomputeTypeError::NonPermutation),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(perm)
+    }
+}
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &PermutationOperator, rhs: &Ket<T>| -> Ket<T> {
+    unwrap_or_panic(lhs.checked_apply(rhs))
+});
+
+auto_ops::impl_op_ex!(*|lhs: &PermutationOperator,
+                        rhs: &PermutationOperator|
+ -> PermutationOperator { unwrap_or_panic(lhs.checked_mul(rhs)) });
+
+auto_ops::impl_op_ex!(&|lhs: &PermutationOperator,
+                        rhs: &PermutationOperator|
+ -> PermutationOperator { lhs.tensorprod(rhs) });
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &PermutationOperator, rhs: &Operator<T>| -> Operator<T> {
+    &lhs.to_operator() & rhs
+});
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &Operator<T>, rhs: &PermutationOperator| -> Operator<T> {
+    lhs & &rhs.to_operator()
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_permutation() {
+        let x = PermutationOperator::try_from(&gates::x::<f64>()).unwrap();
+        let cnot = PermutationOperator::try_from(&gates::cnot::<f64>()).unwrap();
+        assert_eq!(cnot.perm(), [0, 1, 3, 2]);
+        assert!(PermutationOperator::try_from(&gates::y::<f64>()).is_err());
+        assert!(PermutationOperator::try_from(&gates::h::<f64>()).is_err());
+        assert!(PermutationOperator::new(vec![0, 0]).is_err());
+
+        // CNOT (X & I) CNOT = X & X
+        let xi = &x & &PermutationOperator::identity(2);
+        assert_eq!(&(&cnot * &xi) * &cnot, &x & &x);
+        assert_eq!((&x & &x).to_operator::<f64>(), &gates::x() & &gates::x());
+        assert_eq!(&x & &gates::h::<f64>(), &gates::x() & &gates::h());
+
+        let cycle = PermutationOperator::new(vec![1, 2, 0]).unwrap();
+        assert_eq!(&cycle * &cycle.inverse(), PermutationOperator::identity(3));
+        assert_eq!(
+            cycle.inverse().to_operator::<f64>(),
+            cycle.to_operator().dagger()
+        );
+
+        let ket = Ket::from([1f64, 2., 3.].as_slice());
+        assert_eq!(&cycle * &ket, &cycle.to_operator() * &ket);
+        assert!(cycle.checked_apply(&Ket::from([1f64].as_slice())).is_err());
+    }
+}
@@ -0,0 +1,548 @@
+use crate::prelude::*;
+
+use num::Zero;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::braket::{Orientation, QomputeTypeError};
+use super::ops::{check_shape, unwrap_or_panic};
+
+/// An operator in compressed sparse row (CSR) form.
+///
+/// Row `i` holds the entries `values[row_ptr[i]..row_ptr[i + 1]]`, with
+/// strictly increasing column indices in `col_idx`. Absent entries are zero.
+/// Equality compares entry values, so a stored zero equals an absent entry.
+#[derive(Clone, Debug)]
+pub struct SparseOperator<T: Float> {
+    shape: Shape,
+    row_ptr: Vec<usize>,
+    col_idx: Vec<usize>,
+    values: Vec<Complex<T>>,
+    // Borrowed by `Index` for entries that are not stored.
+    zero: Complex<T>,
+}
+
+impl<T: Float> SparseOperator<T> {
+    pub fn zeros(shape: Shape) -> Self {
+        Self {
+            shape,
+            row_ptr: vec![0; shape.rows + 1],
+            col_idx: Vec::new(),
+            values: Vec::new(),
+            zero: Complex::zero(),
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        Self::from_diag(vec![Complex::from(T::one()); n])
+    }
+
+    pub fn from_diag<I>(diag: I) -> Self
+    where
+        I: IntoIterator<Item = Complex<T>>,
+    {
+        let diag = diag.into_iter().collect::<Vec<_>>();
+        let n = diag.len();
+        unwrap_or_panic(Self::from_triplets(
+            (n, n).into(),
+            diag.into_iter().enumerate().map(|(i, a)| (i, i, a)),
+        ))
+    }
+
+    /// Builds an operator from coordinate (COO) triplets `(row, col, value)`
+    /// in any order. Repeated coordinates are summed, and entries that come
+    /// out as exactly zero are dropped.
+    pub fn from_triplets<I>(shape: Shape, triplets: I) -> Result<Self, QomputeTypeError>
+    where
+        I: IntoIterator<Item = (usize, usize, Complex<T>)>,
+    {
+        let mut triplets = triplets.into_iter().collect::<Vec<_>>();
+
+        if let Some(&(row, col, _)) = triplets
+            .iter()
+            .find(|(row, col, _)| *row >= shape.rows || *col >= shape.cols)
+        {
+            return Err(QomputeTypeError::OutOfBounds { row, col, shape });
+        }
+
+        triplets.sort_by_key(|&(row, col, _)| (row, col));
+
+        let mut op = Self::zeros(shape);
+        let mut counts = vec![0; shape.rows];
+
+        for (row, col, value) in triplets {
+            let last = op.col_idx.len().checked_sub(1);
+            match last {
+                Some(k) if counts[row] > 0 && op.col_idx[k] == col => {
+                    op.values[k] = op.values[k] + value;
+                }
+                _ => {
+                    op.col_idx.push(col);
+                    op.values.push(value);
+                    counts[row] += 1;
+                }
+            }
+        }
+
+        for (i, count) in counts.into_iter().enumerate() {
+            op.row_ptr[i + 1] = op.row_ptr[i] + count;
+        }
+        op.prune();
+
+        Ok(op)
+    }
+
+    /// Drops stored entries that are exactly zero, such as those left behind
+    /// by writing zero through `IndexMut`.
+    pub fn prune(&mut self) {
+        let mut kept = 0;
+        let mut start = 0;
+
+        for i in 0..self.rows() {
+            let end = self.row_ptr[i + 1];
+            for k in start..end {
+                if !self.values[k].is_zero() {
+                    self.col_idx[kept] = self.col_idx[k];
+                    self.values[kept] = self.values[k];
+                    kept += 1;
+                }
+            }
+            start = end;
+            self.row_ptr[i + 1] = kept;
+        }
+
+        self.col_idx.truncate(kept);
+        self.values.truncate(kept);
+    }
+
+    /// The number of stored entries. This can include explicit zeros written
+    /// through `IndexMut`, until [`SparseOperator::prune`] is called.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The stored entries of row `i` as `(col, value)`, by increasing column.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, Complex<T>)> + '_ {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        self.col_idx[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    /// All stored entries as `(row, col, value)`, in row-major order.
+    pub fn triplets(&self) -> impl Iterator<Item = (usize, usize, Complex<T>)> + '_ {
+        (0..self.rows()).flat_map(move |i| self.row(i).map(move |(j, a)| (i, j, a)))
+    }
+
+    fn position(&self, (row, col): (usize, usize)) -> Result<usize, usize> {
+        assert!(
+            row < self.shape.rows && col < self.shape.cols,
+            "index ({}, {}) is out of bounds for shape {}",
+            row,
+            col,
+            self.shape
+        );
+
+        let start = self.row_ptr[row];
+        self.col_idx[start..self.row_ptr[row + 1]]
+            .binary_search(&col)
+            .map(|k| start + k)
+            .map_err(|k| start + k)
+    }
+
+    pub fn to_dense(&self) -> Operator<T> {
+        let mut op = Operator::new_with_shape(self.shape);
+        op.inner.iter_mut().for_each(|a| *a = Complex::zero());
+        self.triplets().for_each(|(i, j, a)| op[(i, j)] = a);
+        op
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        check_shape("add", self.shape, rhs.shape)?;
+        Self::from_triplets(self.shape, self.triplets().chain(rhs.triplets()))
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        check_shape("subtract", self.shape, rhs.shape)?;
+        Self::from_triplets(
+            self.shape,
+            self.triplets()
+                .chain(rhs.triplets().map(|(i, j, a)| (i, j, -a))),
+        )
+    }
+
+    /// Fails if the number of rows or columns of the product overflows a
+    /// `usize`.
+    pub fn checked_tensorprod(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        let overflow = QomputeTypeError::Overflow("take the tensor product");
+        let (lhs_rows, lhs_cols) = self.shape().into();
+        let (rhs_rows, rhs_cols) = rhs.shape().into();
+
+        let rows = lhs_rows.checked_mul(rhs_rows).ok_or(overflow.clone())?;
+        let cols = lhs_cols.checked_mul(rhs_cols).ok_or(overflow)?;
+        let mut out = Self::zeros((rows, cols).into());
+
+        for i0 in 0..lhs_rows {
+            for i1 in 0..rhs_rows {
+                for (j0, a) in self.row(i0) {
+                    for (j1, b) in rhs.row(i1) {
+                        out.col_idx.push(j0 * rhs_cols + j1);
+                        out.values.push(a * b);
+                    }
+                }
+                out.row_ptr[i0 * rhs_rows + i1 + 1] = out.values.len();
+            }
+        }
+
+        Ok(out)
+    }
+
+    pub fn checked_apply(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("apply", (self.cols(), 1).into(), rhs.shape())?;
+
+        Ok(Ket {
+            inner: (0..self.rows()).map(|i| self.row_dot(i, rhs)).collect(),
+        })
+    }
+
+    fn row_dot(&self, i: usize, rhs: &Ket<T>) -> Complex<T> {
+        self.row(i)
+            .fold(Complex::zero(), |acc: Complex<T>, (j, a)| acc + a * rhs[j])
+    }
+
+    /// Sparse-sparse product, accumulating one row at a time (Gustavson's
+    /// algorithm).
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, QomputeTypeError> {
+        check_shape("multiply", (self.cols(), rhs.cols()).into(), rhs.shape)?;
+
+        let mut out = Self::zeros((self.rows(), rhs.cols()).into());
+        let mut acc = vec![Complex::zero(); rhs.cols()];
+        let mut touched = Vec::new();
+
+        for i in 0..self.rows() {
+            for (k, a) in self.row(i) {
+                for (j, b) in rhs.row(k) {
+                    if acc[j].is_zero() {
+                        touched.push(j);
+                    }
+                    acc[j] = acc[j] + a * b;
+                }
+            }
+
+            touched.sort_unstable();
+            touched.dedup();
+            for j in touched.drain(..) {
+                if !acc[j].is_zero() {
+                    out.col_idx.push(j);
+                    out.values.push(acc[j]);
+                }
+                acc[j] = Complex::zero();
+            }
+            out.row_ptr[i + 1] = out.values.len();
+        }
+
+        Ok(out)
+    }
+
+    pub fn checked_mul_dense(&self, rhs: &Operator<T>) -> Result<Operator<T>, QomputeTypeError> {
+        check_shape("multiply", (self.cols(), rhs.cols()).into(), rhs.shape())?;
+
+        let mut out = Operator::new_with_shape((self.rows(), rhs.cols()).into());
+        out.inner.iter_mut().for_each(|a| *a = Complex::zero());
+
+        for (i, k, a) in self.triplets() {
+            for j in 0..rhs.cols() {
+                out[(i, j)] = out[(i, j)] + a * rhs[(k, j)];
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// `lhs * self` for a dense `lhs`.
+    pub fn checked_rmul_dense(&self, lhs: &Operator<T>) -> Result<Operator<T>, QomputeTypeError> {
+        check_shape("multiply", (lhs.cols(), self.cols()).into(), self.shape)?;
+
+        let mut out = Operator::new_with_shape((lhs.rows(), self.cols()).into());
+        out.inner.iter_mut().for_each(|a| *a = Complex::zero());
+
+        for (k, j, b) in self.triplets() {
+            for i in 0..lhs.rows() {
+                out[(i, j)] = out[(i, j)] + lhs[(i, k)] * b;
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Float + Send + Sync> SparseOperator<T> {
+    /// Like [`SparseOperator::checked_apply`], but splits the rows across the
+    /// rayon thread pool.
+    pub fn par_checked_apply(&self, rhs: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("apply", (self.cols(), 1).into(), rhs.shape())?;
+
+        let inner = (0..self.rows())
+            .into_par_iter()
+            .map(|i| self.row_dot(i, rhs))
+            .collect::<Vec<_>>();
+
+        Ok(Ket {
+            inner: inner.into_iter().collect(),
+        })
+    }
+}
+
+impl<T: Float> ComplexObject<T> for SparseOperator<T> {
+    type ConjugateTranspose = SparseOperator<T>;
+    type InnerProduct = SparseOperator<T>;
+    type OuterProduct = SparseOperator<T>;
+    type IndexType = (usize, usize);
+
+    const ORIENTATION: Orientation = Orientation::NonOriented;
+
+    fn dagger(&self) -> Self::ConjugateTranspose {
+        let Shape { rows, cols } = self.shape;
+        let mut out = Self::zeros(self.shape.transpose());
+
+        // Count the entries of each column, then scatter rows in order so the
+        // transposed rows come out sorted.
+        for &j in &self.col_idx {
+            out.row_ptr[j + 1] += 1;
+        }
+        for j in 0..cols {
+            out.row_ptr[j + 1] += out.row_ptr[j];
+        }
+
+        let mut next = out.row_ptr[..cols].to_vec();
+        out.col_idx = vec![0; self.nnz()];
+        out.values = vec![Complex::zero(); self.nnz()];
+
+        for i in 0..rows {
+            for (j, a) in self.row(i) {
+                out.col_idx[next[j]] = i;
+                out.values[next[j]] = a.conj();
+                next[j] += 1;
+            }
+        }
+
+        out
+    }
+
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    fn hermitian(&self) -> bool {
+        self.shape == self.shape.transpose()
+            && self.triplets().all(|(i, j, a)| self[(j, i)] == a.conj())
+    }
+
+    fn index(&self, idx: Self::IndexType) -> &Complex<T> {
+        &self[idx]
+    }
+
+    fn index_mut(&mut self, idx: Self::IndexType) -> &mut Complex<T> {
+        &mut self[idx]
+    }
+
+    fn tensorprod(&self, rhs: &Self) -> Self {
+        unwrap_or_panic(self.checked_tensorprod(rhs))
+    }
+}
+
+impl<T: Float> std::ops::Index<(usize, usize)> for SparseOperator<T> {
+    type Output = Complex<T>;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        match self.position(index) {
+            Ok(k) => &self.values[k],
+            Err(_) => &self.zero,
+        }
+    }
+}
+
+/// Writing to an entry that is not stored inserts it, which costs `O(nnz)`.
+/// The entry stays stored even if the value written is zero; call
+/// [`SparseOperator::prune`] to drop such entries.
+impl<T: Float> std::ops::IndexMut<(usize, usize)> for SparseOperator<T> {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        match self.position(index) {
+            Ok(k) => &mut self.values[k],
+            Err(k) => {
+                self.col_idx.insert(k, index.1);
+                self.values.insert(k, Complex::zero());
+                self.row_ptr[index.0 + 1..].iter_mut().for_each(|p| *p += 1);
+                &mut self.values[k]
+            }
+        }
+    }
+}
+
+impl<T: Float> PartialEq for SparseOperator<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let nonzero = |(_, _, a): &(usize, usize, Complex<T>)| !a.is_zero();
+
+        self.shape == other.shape
+            && self
+                .triplets()
+                .filter(nonzero)
+                .eq(other.triplets().filter(nonzero))
+    }
+}
+
+impl<T: Float> From<&Operator<T>> for SparseOperator<T> {
+    fn from(op: &Operator<T>) -> Self {
+        unwrap_or_panic(Self::from_triplets(
+            op.shape(),
+            (0..op.rows())
+                .flat_map(|i| (0..op.cols()).map(move |j| (i, j)))
+                .map(|(i, j)| (i, j, op[(i, j)]))
+                .filter(|(_, _, a)| !a.is_zero()),
+        ))
+    }
+}
+
+impl<T: Float> From<&SparseOperator<T>> for Operator<T> {
+    fn from(op: &SparseOperator<T>) -> Self {
+        op.to_dense()
+    }
+}
+
+auto_ops::impl_op_ex!(+ <T: Float> |lhs: &SparseOperator<T>, rhs: &SparseOperator<T>| -> SparseOperator<T> {
+    unwrap_or_panic(lhs.checked_add(rhs))
+});
+
+auto_ops::impl_op_ex!(- <T: Float> |lhs: &SparseOperator<T>, rhs: &SparseOperator<T>| -> SparseOperator<T> {
+    unwrap_or_panic(lhs.checked_sub(rhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &SparseOperator<T>, rhs: &SparseOperator<T>| -> SparseOperator<T> {
+    unwrap_or_panic(lhs.checked_mul(rhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &SparseOperator<T>, rhs: &Operator<T>| -> Operator<T> {
+    unwrap_or_panic(lhs.checked_mul_dense(rhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &Operator<T>, rhs: &SparseOperator<T>| -> Operator<T> {
+    unwrap_or_panic(rhs.checked_rmul_dense(lhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &SparseOperator<T>, rhs: &Ket<T>| -> Ket<T> {
+    unwrap_or_panic(lhs.checked_apply(rhs))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &SparseOperator<T>, rhs: Complex<T>| -> SparseOperator<T> {
+    let mut out = lhs.clone();
+    out.values.iter_mut().for_each(|a| *a = *a * rhs);
+    out.prune();
+    out
+});
+
+auto_ops::impl_op_ex!(& <T: Float> |lhs: &SparseOperator<T>, rhs: &SparseOperator<T>| -> SparseOperator<T> {
+    unwrap_or_panic(lhs.checked_tensorprod(rhs))
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_sparse() {
+        let dense = Operator::from([
+            [
+                Complex::new(0f64, 0.),
+                Complex::new(2., 1.),
+                Complex::new(0., 0.),
+            ],
+            [
+                Complex::new(0., 0.),
+                Complex::new(0., 0.),
+                Complex::new(0., 0.),
+            ],
+            [
+                Complex::new(-1., 0.),
+                Complex::new(0., 0.),
+                Complex::new(0., 3.),
+            ],
+        ]);
+        let sparse = SparseOperator::from(&dense);
+        assert_eq!(sparse.nnz(), 3);
+        assert_eq!(sparse.to_dense(), dense);
+        assert_eq!(sparse[(2, 2)], Complex::new(0., 3.));
+        assert_eq!(sparse[(1, 1)], Complex::from(0.));
+
+        assert_eq!(sparse.dagger().to_dense(), dense.dagger());
+        assert_eq!((&sparse * &sparse).to_dense(), &dense * &dense);
+        assert_eq!(&sparse * &dense, &dense * &dense);
+        assert_eq!(&dense * &sparse, &dense * &dense);
+        assert_eq!((&sparse - &sparse).nnz(), 0);
+
+        let ket = Ket::from([1f64, 2., 3.].as_slice());
+        assert_eq!(&sparse * &ket, &dense * &ket);
+        assert!(sparse.checked_apply(&Ket::from([1f64].as_slice())).is_err());
+        #[cfg(feature = "rayon")]
+        assert_eq!(sparse.par_checked_apply(&ket).unwrap(), &dense * &ket);
+
+        let x = SparseOperator::from(&gates::x::<f64>());
+        assert_eq!((&x & &sparse).to_dense(), &gates::x() & &dense);
+        assert!(x.hermitian() && !sparse.hermitian());
+
+        let mut op = SparseOperator::<f64>::zeros((2, 3).into());
+        op[(1, 2)] = Complex::from(4.);
+        op[(1, 0)] = Complex::from(5.);
+        op[(0, 1)] = Complex::from(6.);
+        assert_eq!(op.to_dense(), Operator::from([[0., 6., 0.], [5., 0., 4.]]));
+
+        let mut zeros = SparseOperator::<f64>::zeros((2, 2).into());
+        zeros[(0, 1)] = Complex::from(0.);
+        assert_eq!(zeros.nnz(), 1);
+        assert_eq!(zeros, SparseOperator::zeros((2, 2).into()));
+        zeros.prune();
+        assert_eq!(zeros.nnz(), 0);
+
+        let wide = SparseOperator::<f64>::zeros((1, usize::MAX / 2 + 1).into());
+        assert!(wide.checked_tensorprod(&x).is_err());
+
+        let summed = SparseOperator::from_triplets(
+            (2, 2).into(),
+            [
+                (1, 1, Complex::from(1f64)),
+                (0, 0, Complex::from(2.)),
+                (1, 1, Complex::from(1.)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            summed,
+            SparseOperator::from_diag([2., 2.].map(Complex::from))
+        );
+        assert!(
+            SparseOperator::from_triplets((2, 2).into(), [(2, 0, Complex::from(1f64))]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_large_hamiltonian() {
+        // A 16 qubit Ising chain never materializes its 2^32 dense entries.
+        let n = 16;
+        let h = (0..n - 1).fold(PauliSum::new(n), |h, q| {
+            let mut zz = PauliString::single(n, q, Pauli::Z);
+            zz.set(q + 1, Pauli::Z);
+            h.with_term(Complex::from(-1f64), zz)
+                .with_term(Complex::from(-0.5), PauliString::single(n, q, Pauli::X))
+        });
+        let sparse = h.to_sparse();
+        assert_eq!(sparse.nnz(), (1 << n) * n);
+        assert!(sparse.hermitian());
+
+        let mut ket = Ket::from(vec![Complex::from(0f64); 1 << n].as_slice());
+        ket[0] = Complex::from(1.);
+        let applied = &sparse * &ket;
+        assert!((&ket.dagger() * &applied).approx_eq(&h.expectation(&ket).unwrap()));
+    }
+}
@@ -0,0 +1,770 @@
+use std::ops::Range;
+
+use num::Zero;
+
+use crate::prelude::*;
+
+use super::braket::QomputeTypeError;
+use super::ops::{check_shape, unwrap_or_panic};
+
+/// A borrowed, possibly strided run of amplitudes, read as a column vector.
+///
+/// Entry `i` lives at `data[i * stride]`, so a view can cover a contiguous
+/// slice of a `Ket` as well as a column of an `Operator`.
+#[derive(Copy, Clone, Debug)]
+pub struct KetView<'a, T: Float> {
+    data: &'a [Complex<T>],
+    len: usize,
+    stride: usize,
+}
+
+/// The mutable counterpart of [`KetView`].
+#[derive(Debug)]
+pub struct KetViewMut<'a, T: Float> {
+    data: &'a mut [Complex<T>],
+    len: usize,
+    stride: usize,
+}
+
+/// A borrowed, possibly strided sub-block of a matrix, with entry `(i, j)` at
+/// `data[i * strides.0 + j * strides.1]`.
+#[derive(Copy, Clone, Debug)]
+pub struct OperatorView<'a, T: Float> {
+    data: &'a [Complex<T>],
+    shape: Shape,
+    strides: (usize, usize),
+}
+
+/// The mutable counterpart of [`OperatorView`].
+#[derive(Debug)]
+pub struct OperatorViewMut<'a, T: Float> {
+    data: &'a mut [Complex<T>],
+    shape: Shape,
+    strides: (usize, usize),
+}
+
+fn check_range(range: &Range<usize>, len: usize) {
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "range {:?} out of bounds for length {}",
+        range,
+        len
+    );
+}
+
+macro_rules! impl_ket_view {
+    ($t:ident) => {
+        impl<'a, T: Float> $t<'a, T> {
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            pub fn shape(&self) -> Shape {
+                (self.len, 1).into()
+            }
+
+            pub fn iter(&self) -> impl Iterator<Item = &Complex<T>> + '_ {
+                self.data.iter().step_by(self.stride).take(self.len)
+            }
+
+            pub fn slice(&self, range: Range<usize>) -> KetView<'_, T> {
+                check_range(&range, self.len);
+                KetView {
+                    data: self.data.get(range.start * self.stride..).unwrap_or(&[]),
+                    len: range.len(),
+                    stride: self.stride,
+                }
+            }
+
+            pub fn to_ket(&self) -> Ket<T> {
+                Ket {
+                    inner: self.iter().copied().collect(),
+                }
+            }
+
+            pub fn dagger(&self) -> Bra<T> {
+                Bra {
+                    inner: self.iter().map(|a| a.conj()).collect(),
+                }
+            }
+        }
+
+        impl<'a, T: Float> std::ops::Index<usize> for $t<'a, T> {
+            type Output = Complex<T>;
+
+            fn index(&self, index: usize) -> &Self::Output {
+                assert!(
+                    index < self.len,
+                    "index {} out of bounds for length {}",
+                    index,
+                    self.len
+                );
+                &self.data[index * self.stride]
+            }
+        }
+    };
+}
+
+macro_rules! impl_operator_view {
+    ($t:ident) => {
+        impl<'a, T: Float> $t<'a, T> {
+            pub fn shape(&self) -> Shape {
+                self.shape
+            }
+
+            pub fn rows(&self) -> usize {
+                self.shape.rows
+            }
+
+            pub fn cols(&self) -> usize {
+                self.shape.cols
+            }
+
+            pub fn block(&self, rows: Range<usize>, cols: Range<usize>) -> OperatorView<'_, T> {
+                check_range(&rows, self.shape.rows);
+                check_range(&cols, self.shape.cols);
+
+                let offset = rows.start * self.strides.0 + cols.start * self.strides.1;
+                OperatorView {
+                    data: self.data.get(offset..).unwrap_or(&[]),
+                    shape: (rows.len(), cols.len()).into(),
+                    strides: self.strides,
+                }
+            }
+
+            /// Row `i` as a `1 x cols` block.
+            pub fn row(&self, i: usize) -> OperatorView<'_, T> {
+                self.block(i..i + 1, 0..self.shape.cols)
+            }
+
+            /// Column `j` as a ket.
+            pub fn col(&self, j: usize) -> KetView<'_, T> {
+                assert!(
+                    j < self.shape.cols,
+                    "column {} out of bounds for {} columns",
+                    j,
+                    self.shape.cols
+                );
+                KetView {
+                    data: &self.data[j * self.strides.1..],
+                    len: self.shape.rows,
+                    stride: self.strides.0,
+                }
+            }
+
+            pub fn to_operator(&self) -> Operator<T> {
+                let (rows, cols) = self.shape.into();
+                Operator {
+                    shape: self.shape,
+                    inner: (0..rows)
+                        .flat_map(|i| (0..cols).map(move |j| (i, j)))
+                        .map(|idx| self[idx])
+                        .collect(),
+                }
+            }
+        }
+
+        impl<'a, T: Float> std::ops::Index<(usize, usize)> for $t<'a, T> {
+            type Output = Complex<T>;
+
+            fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+                assert!(
+                    i < self.shape.rows && j < self.shape.cols,
+                    "index {:?} out of bounds for shape {}",
+                    (i, j),
+                    self.shape
+                );
+                &self.data[i * self.strides.0 + j * self.strides.1]
+            }
+        }
+    };
+}
+
+impl_ket_view!(KetView);
+impl_ket_view!(KetViewMut);
+impl_operator_view!(OperatorView);
+impl_operator_view!(OperatorViewMut);
+
+impl<'a, T: Float> KetView<'a, T> {
+    pub fn checked_add(self, rhs: KetView<'_, T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("add", self.shape(), rhs.shape())?;
+        Ok(Ket {
+            inner: self.iter().zip(rhs.iter()).map(|(a, b)| a + b).collect(),
+        })
+    }
+
+    pub fn checked_sub(self, rhs: KetView<'_, T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("subtract", self.shape(), rhs.shape())?;
+        Ok(Ket {
+            inner: self.iter().zip(rhs.iter()).map(|(a, b)| a - b).collect(),
+        })
+    }
+
+    pub fn checked_tensorprod(self, rhs: KetView<'_, T>) -> Result<Ket<T>, QomputeTypeError> {
+        self.len
+            .checked_mul(rhs.len)
+            .ok_or(QomputeTypeError::Overflow("take the tensor product"))?;
+        Ok(Ket {
+            inner: self
+                .iter()
+                .flat_map(|a| rhs.iter().map(move |b| a * b))
+                .collect(),
+        })
+    }
+}
+
+impl<'a, T: Float> OperatorView<'a, T> {
+    /// Swaps the strides, viewing the transpose without copying.
+    pub fn transpose(self) -> Self {
+        Self {
+            data: self.data,
+            shape: self.shape.transpose(),
+            strides: (self.strides.1, self.strides.0),
+        }
+    }
+
+    pub fn checked_add(self, rhs: OperatorView<'_, T>) -> Result<Operator<T>, QomputeTypeError> {
+        check_shape("add", self.shape, rhs.shape)?;
+        Ok(self.zip_with(rhs, |a, b| a + b))
+    }
+
+    pub fn checked_sub(self, rhs: OperatorView<'_, T>) -> Result<Operator<T>, QomputeTypeError> {
+        check_shape("subtract", self.shape, rhs.shape)?;
+        Ok(self.zip_with(rhs, |a, b| a - b))
+    }
+
+    pub fn checked_mul(self, rhs: OperatorView<'_, T>) -> Result<Operator<T>, QomputeTypeError> {
+        let (rows, inner) = self.shape.into();
+        let cols = rhs.shape.cols;
+        check_shape("multiply", (inner, cols).into(), rhs.shape)?;
+
+        let mut op = Operator::new_with_shape((rows, cols).into());
+
+        for i in 0..rows {
+            for j in 0..cols {
+                op[(i, j)] = (0..inner).map(|k| self[(i, k)] * rhs[(k, j)]).sum();
+            }
+        }
+
+        Ok(op)
+    }
+
+    pub fn checked_apply(self, rhs: KetView<'_, T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_shape("apply", (self.shape.cols, 1).into(), rhs.shape())?;
+        Ok(Ket {
+            inner: (0..self.shape.rows)
+                .map(|i| rhs.iter().enumerate().map(|(k, a)| self[(i, k)] * a).sum())
+                .collect(),
+        })
+    }
+
+    pub fn checked_tensorprod(
+        self,
+        rhs: OperatorView<'_, T>,
+    ) -> Result<Operator<T>, QomputeTypeError> {
+        self.shape
+            .size()
+            .checked_mul(rhs.shape.size())
+            .ok_or(QomputeTypeError::Overflow("take the tensor product"))?;
+
+        let (lhs_rows, lhs_cols) = self.shape.into();
+        let (rhs_rows, rhs_cols) = rhs.shape.into();
+        let mut op = Operator::new_with_shape((lhs_rows * rhs_rows, lhs_cols * rhs_cols).into());
+
+        for i in 0..op.rows() {
+            for j in 0..op.cols() {
+                op[(i, j)] = self[(i / rhs_rows, j / rhs_cols)] * rhs[(i % rhs_rows, j % rhs_cols)];
+            }
+        }
+
+        Ok(op)
+    }
+
+    fn zip_with(
+        self,
+        rhs: OperatorView<'_, T>,
+        f: impl Fn(Complex<T>, Complex<T>) -> Complex<T>,
+    ) -> Operator<T> {
+        let (rows, cols) = self.shape.into();
+        Operator {
+            shape: self.shape,
+            inner: (0..rows)
+                .flat_map(|i| (0..cols).map(move |j| (i, j)))
+                .map(|idx| f(self[idx], rhs[idx]))
+                .collect(),
+        }
+    }
+}
+
+impl<'a, T: Float> KetViewMut<'a, T> {
+    pub fn as_view(&self) -> KetView<'_, T> {
+        KetView {
+            data: self.data,
+            len: self.len,
+            stride: self.stride,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Complex<T>> + '_ {
+        self.data.iter_mut().step_by(self.stride).take(self.len)
+    }
+
+    pub fn slice_mut(&mut self, range: Range<usize>) -> KetViewMut<'_, T> {
+        check_range(&range, self.len);
+        KetViewMut {
+            data: self
+                .data
+                .get_mut(range.start * self.stride..)
+                .unwrap_or(&mut []),
+            len: range.len(),
+            stride: self.stride,
+        }
+    }
+
+    /// Overwrites the viewed amplitudes with those of `src`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ.
+    pub fn assign<'b>(&mut self, src: impl Into<KetView<'b, T>>)
+    where
+        T: 'b,
+    {
+        let src = src.into();
+        unwrap_or_panic(check_shape("assign", self.shape(), src.shape()));
+        self.iter_mut().zip(src.iter()).for_each(|(a, b)| *a = *b);
+    }
+}
+
+impl<'a, T: Float> OperatorViewMut<'a, T> {
+    pub fn as_view(&self) -> OperatorView<'_, T> {
+        OperatorView {
+            data: self.data,
+            shape: self.shape,
+            strides: self.strides,
+        }
+    }
+
+    pub fn block_mut(&mut self, rows: Range<usize>, cols: Range<usize>) -> OperatorViewMut<'_, T> {
+        check_range(&rows, self.shape.rows);
+        check_range(&cols, self.shape.cols);
+
+        let offset = rows.start * self.strides.0 + cols.start * self.strides.1;
+        OperatorViewMut {
+            data: self.data.get_mut(offset..).unwrap_or(&mut []),
+            shape: (rows.len(), cols.len()).into(),
+            strides: self.strides,
+        }
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> OperatorViewMut<'_, T> {
+        let cols = self.shape.cols;
+        self.block_mut(i..i + 1, 0..cols)
+    }
+
+    pub fn col_mut(&mut self, j: usize) -> KetViewMut<'_, T> {
+        assert!(
+            j < self.shape.cols,
+            "column {} out of bounds for {} columns",
+            j,
+            self.shape.cols
+        );
+        KetViewMut {
+            data: &mut self.data[j * self.strides.1..],
+            len: self.shape.rows,
+            stride: self.strides.0,
+        }
+    }
+
+    /// Overwrites the viewed block with `src`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shapes differ.
+    pub fn assign<'b>(&mut self, src: impl Into<OperatorView<'b, T>>)
+    where
+        T: 'b,
+    {
+        let src = src.into();
+        unwrap_or_panic(check_shape("assign", self.shape, src.shape));
+        self.update(|idx, a| *a = src[idx]);
+    }
+
+    fn update(&mut self, mut f: impl FnMut((usize, usize), &mut Complex<T>)) {
+        let (rows, cols) = self.shape.into();
+        for i in 0..rows {
+            for j in 0..cols {
+                f(
+                    (i, j),
+                    &mut self.data[i * self.strides.0 + j * self.strides.1],
+                );
+            }
+        }
+    }
+}
+
+impl<'a, T: Float> std::ops::IndexMut<usize> for KetViewMut<'a, T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        assert!(
+            index < self.len,
+            "index {} out of bounds for length {}",
+            index,
+            self.len
+        );
+        &mut self.data[index * self.stride]
+    }
+}
+
+impl<'a, T: Float> std::ops::IndexMut<(usize, usize)> for OperatorViewMut<'a, T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(
+            i < self.shape.rows && j < self.shape.cols,
+            "index {:?} out of bounds for shape {}",
+            (i, j),
+            self.shape
+        );
+        &mut self.data[i * self.strides.0 + j * self.strides.1]
+    }
+}
+
+impl<T: Float> Ket<T> {
+    pub fn view(&self) -> KetView<'_, T> {
+        KetView::from(self)
+    }
+
+    pub fn view_mut(&mut self) -> KetViewMut<'_, T> {
+        KetViewMut {
+            len: self.inner.len(),
+            data: &mut self.inner,
+            stride: 1,
+        }
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> KetView<'_, T> {
+        check_range(&range, self.inner.len());
+        KetView {
+            data: &self.inner[range.start..],
+            len: range.len(),
+            stride: 1,
+        }
+    }
+
+    pub fn slice_mut(&mut self, range: Range<usize>) -> KetViewMut<'_, T> {
+        check_range(&range, self.inner.len());
+        KetViewMut {
+            data: &mut self.inner[range.start..],
+            len: range.len(),
+            stride: 1,
+        }
+    }
+}
+
+impl<T: Float> Operator<T> {
+    pub fn view(&self) -> OperatorView<'_, T> {
+        OperatorView::from(self)
+    }
+
+    pub fn view_mut(&mut self) -> OperatorViewMut<'_, T> {
+        OperatorViewMut {
+            strides: (self.shape.cols, 1),
+            shape: self.shape,
+            data: &mut self.inner,
+        }
+    }
+
+    pub fn block(&self, rows: Range<usize>, cols: Range<usize>) -> OperatorView<'_, T> {
+        check_range(&rows, self.shape.rows);
+        check_range(&cols, self.shape.cols);
+        OperatorView {
+            data: self
+                .inner
+                .get(rows.start * self.shape.cols + cols.start..)
+                .unwrap_or(&[]),
+            shape: (rows.len(), cols.len()).into(),
+            strides: (self.shape.cols, 1),
+        }
+    }
+
+    pub fn block_mut(&mut self, rows: Range<usize>, cols: Range<usize>) -> OperatorViewMut<'_, T> {
+        check_range(&rows, self.shape.rows);
+        check_range(&cols, self.shape.cols);
+        OperatorViewMut {
+            strides: (self.shape.cols, 1),
+            data: self
+                .inner
+                .get_mut(rows.start * self.shape.cols + cols.start..)
+                .unwrap_or(&mut []),
+            shape: (rows.len(), cols.len()).into(),
+        }
+    }
+
+    /// Row `i` as a `1 x cols` block.
+    pub fn row(&self, i: usize) -> OperatorView<'_, T> {
+        self.block(i..i + 1, 0..self.shape.cols)
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> OperatorViewMut<'_, T> {
+        let cols = self.shape.cols;
+        self.block_mut(i..i + 1, 0..cols)
+    }
+
+    /// Column `j` as a ket.
+    pub fn col(&self, j: usize) -> KetView<'_, T> {
+        assert!(
+            j < self.shape.cols,
+            "column {} out of bounds for {} columns",
+            j,
+            self.shape.cols
+        );
+        KetView {
+            data: &self.inner[j..],
+            len: self.shape.rows,
+            stride: self.shape.cols,
+        }
+    }
+
+    pub fn col_mut(&mut self, j: usize) -> KetViewMut<'_, T> {
+        assert!(
+            j < self.shape.cols,
+            "column {} out of bounds for {} columns",
+            j,
+            self.shape.cols
+        );
+        KetViewMut {
+            len: self.shape.rows,
+            stride: self.shape.cols,
+            data: &mut self.inner[j..],
+        }
+    }
+}
+
+impl<'a, T: Float> From<&'a Ket<T>> for KetView<'a, T> {
+    fn from(ket: &'a Ket<T>) -> Self {
+        Self {
+            data: &ket.inner,
+            len: ket.inner.len(),
+            stride: 1,
+        }
+    }
+}
+
+impl<'a, 'b, T: Float> From<&'a KetViewMut<'b, T>> for KetView<'a, T> {
+    fn from(view: &'a KetViewMut<'b, T>) -> Self {
+        view.as_view()
+    }
+}
+
+impl<'a, T: Float> From<&'a Operator<T>> for OperatorView<'a, T> {
+    fn from(op: &'a Operator<T>) -> Self {
+        Self {
+            data: &op.inner,
+            shape: op.shape,
+            strides: (op.shape.cols, 1),
+        }
+    }
+}
+
+impl<'a, 'b, T: Float> From<&'a OperatorViewMut<'b, T>> for OperatorView<'a, T> {
+    fn from(view: &'a OperatorViewMut<'b, T>) -> Self {
+        view.as_view()
+    }
+}
+
+/// Implements a binary operator between borrowed operands, going through the
+/// view's checked method.
+macro_rules! impl_view_op {
+    ($trait:ident, $method:ident, $checked:ident, $view:ident, $out:ty; $(($lhs:ty, $rhs:ty)),+) => {
+        $(
+            impl<'a, 'b, T: Float> std::ops::$trait<$rhs> for $lhs {
+                type Output = $out;
+
+                fn $method(self, rhs: $rhs) -> Self::Output {
+                    unwrap_or_panic($view::from(self).$checked(rhs.into()))
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_view_ops {
+    ($trait:ident, $method:ident, $checked:ident, $view:ident, $owned:ident) => {
+        impl_view_op!(
+            $trait, $method, $checked, $view, $owned<T>;
+            ($view<'a, T>, $view<'b, T>),
+            ($view<'a, T>, &'b $owned<T>),
+            (&'a $owned<T>, $view<'b, T>)
+        );
+    };
+}
+
+impl_view_ops!(Add, add, checked_add, KetView, Ket);
+impl_view_ops!(Sub, sub, checked_sub, KetView, Ket);
+impl_view_ops!(BitAnd, bitand, checked_tensorprod, KetView, Ket);
+impl_view_ops!(Add, add, checked_add, OperatorView, Operator);
+impl_view_ops!(Sub, sub, checked_sub, OperatorView, Operator);
+impl_view_ops!(Mul, mul, checked_mul, OperatorView, Operator);
+impl_view_ops!(BitAnd, bitand, checked_tensorprod, OperatorView, Operator);
+impl_view_op!(
+    Mul, mul, checked_apply, OperatorView, Ket<T>;
+    (OperatorView<'a, T>, KetView<'b, T>),
+    (OperatorView<'a, T>, &'b Ket<T>),
+    (&'a Operator<T>, KetView<'b, T>)
+);
+
+impl<T: Float> std::ops::Mul<KetView<'_, T>> for &Bra<T> {
+    type Output = Complex<T>;
+
+    fn mul(self, rhs: KetView<'_, T>) -> Self::Output {
+        unwrap_or_panic(check_shape(
+            "take the inner product",
+            (self.cols(), 1).into(),
+            rhs.shape(),
+        ));
+        self.inner
+            .iter()
+            .zip(rhs.iter())
+            .fold(Complex::<T>::zero(), |acc, (a, b)| acc + a * b)
+    }
+}
+
+impl<'a, T: Float> std::ops::Mul<Complex<T>> for KetView<'a, T> {
+    type Output = Ket<T>;
+
+    fn mul(self, rhs: Complex<T>) -> Self::Output {
+        Ket {
+            inner: self.iter().map(|a| a * rhs).collect(),
+        }
+    }
+}
+
+impl<'a, T: Float> std::ops::Mul<Complex<T>> for OperatorView<'a, T> {
+    type Output = Operator<T>;
+
+    fn mul(self, rhs: Complex<T>) -> Self::Output {
+        self.zip_with(self, |a, _| a * rhs)
+    }
+}
+
+impl<'a, T: Float> std::ops::Neg for KetView<'a, T> {
+    type Output = Ket<T>;
+
+    fn neg(self) -> Self::Output {
+        Ket {
+            inner: self.iter().map(|a| -a).collect(),
+        }
+    }
+}
+
+impl<'a, T: Float> std::ops::Neg for OperatorView<'a, T> {
+    type Output = Operator<T>;
+
+    fn neg(self) -> Self::Output {
+        self.zip_with(self, |a, _| -a)
+    }
+}
+
+impl<'a, 'b, T: Float> std::ops::AddAssign<KetView<'b, T>> for KetViewMut<'a, T> {
+    fn add_assign(&mut self, rhs: KetView<'b, T>) {
+        unwrap_or_panic(check_shape("add", self.shape(), rhs.shape()));
+        self.iter_mut()
+            .zip(rhs.iter())
+            .for_each(|(a, b)| *a = *a + b);
+    }
+}
+
+impl<'a, 'b, T: Float> std::ops::SubAssign<KetView<'b, T>> for KetViewMut<'a, T> {
+    fn sub_assign(&mut self, rhs: KetView<'b, T>) {
+        unwrap_or_panic(check_shape("subtract", self.shape(), rhs.shape()));
+        self.iter_mut()
+            .zip(rhs.iter())
+            .for_each(|(a, b)| *a = *a - b);
+    }
+}
+
+impl<'a, T: Float> std::ops::MulAssign<Complex<T>> for KetViewMut<'a, T> {
+    fn mul_assign(&mut self, rhs: Complex<T>) {
+        self.iter_mut().for_each(|a| *a = *a * rhs);
+    }
+}
+
+impl<'a, 'b, T: Float> std::ops::AddAssign<OperatorView<'b, T>> for OperatorViewMut<'a, T> {
+    fn add_assign(&mut self, rhs: OperatorView<'b, T>) {
+        unwrap_or_panic(check_shape("add", self.shape, rhs.shape));
+        self.update(|idx, a| *a = *a + rhs[idx]);
+    }
+}
+
+impl<'a, 'b, T: Float> std::ops::SubAssign<OperatorView<'b, T>> for OperatorViewMut<'a, T> {
+    fn sub_assign(&mut self, rhs: OperatorView<'b, T>) {
+        unwrap_or_panic(check_shape("subtract", self.shape, rhs.shape));
+        self.update(|idx, a| *a = *a - rhs[idx]);
+    }
+}
+
+impl<'a, T: Float> std::ops::MulAssign<Complex<T>> for OperatorViewMut<'a, T> {
+    fn mul_assign(&mut self, rhs: Complex<T>) {
+        self.update(|_, a| *a = *a * rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_views() {
+        let op = Operator::from([[1f64, 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+
+        assert_eq!(op.col(1).to_ket(), Ket::from([2f64, 5., 8.].as_slice()));
+        assert_eq!(op.row(2).to_operator(), Operator::from([[7f64, 8., 9.]]));
+
+        let block = op.block(1..3, 0..2);
+        assert_eq!(block.to_operator(), Operator::from([[4f64, 5.], [7., 8.]]));
+        assert_eq!(
+            block.transpose().to_operator(),
+            Operator::from([[4f64, 7.], [5., 8.]])
+        );
+        assert_eq!(
+            block.col(1).slice(1..2).to_ket(),
+            Ket::from([8f64].as_slice())
+        );
+
+        assert_eq!(
+            block * block.transpose(),
+            Operator::from([[41f64, 68.], [68., 113.]])
+        );
+        assert_eq!(&op * op.col(0), Ket::from([30f64, 66., 102.].as_slice()));
+        assert_eq!(
+            op.col(0) + &Ket::from([1f64, 1., 1.].as_slice()),
+            Ket::from([2f64, 5., 8.].as_slice())
+        );
+        assert_eq!(&op.col(2).dagger() * op.col(2), Complex::from(126.));
+        assert_eq!((op.row(0) & op.block(0..1, 0..1)).shape(), (1, 3).into());
+        assert!(op.block(0..2, 0..2).checked_add(op.row(0)).is_err());
+    }
+
+    #[test]
+    fn test_mut_views() {
+        let mut op = Operator::from([[1f64, 2.], [3., 4.]]);
+        let ket = Ket::from([10f64, 20.].as_slice());
+
+        op.col_mut(0).assign(&ket);
+        let mut row = op.row_mut(1);
+        row *= Complex::from(-1.);
+        assert_eq!(&op, &Operator::from([[10f64, 2.], [-20., -4.]]));
+
+        let mut big = Ket::from([0f64; 8].as_slice());
+        big.slice_mut(2..4).assign(&ket);
+        let mut tail = big.slice_mut(4..8);
+        tail.slice_mut(1..3).assign(&ket);
+        tail += (ket.view().slice(0..2) & Ket::from([1f64, 1.].as_slice()).view()).view();
+        assert_eq!(
+            &big.inner[..],
+            &Ket::from([0f64, 0., 10., 20., 10., 20., 40., 20.].as_slice()).inner[..]
+        );
+    }
+}
@@ -1,7 +1,12 @@
 #[macro_use]
 pub mod complex;
 
+pub mod circuit;
+pub mod linalg;
+pub mod noise;
+pub mod pauli;
 pub mod prelude;
+pub mod state;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,385 @@
+use crate::prelude::*;
+
+use num::{One, Zero};
+
+use super::check_square;
+use crate::complex::braket::QomputeTypeError;
+
+const MAX_SWEEPS: usize = 64;
+const MAX_QR_ITERATIONS: usize = 64;
+
+impl<T: Float> Operator<T> {
+    /// Diagonalizes a Hermitian operator with cyclic complex Jacobi rotations,
+    /// returning eigenvalues in ascending order with matching normalized
+    /// eigenvectors. Operators that are Hermitian only up to rounding are
+    /// accepted and symmetrized first.
+    pub fn eigh(&self) -> Result<(Vec<T>, Vec<Ket<T>>), QomputeTypeError> {
+        check_square(self, "diagonalize")?;
+
+        if !super::norm_one(self).is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        let n = self.rows();
+        let mut a = super::hermitian_part(self)?;
+        let mut v = Operator::new_with_shape(self.shape());
+
+        let off = |a: &Operator<T>| {
+            (0..n)
+                .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+                .fold(T::zero(), |acc, (p, q)| acc + a[(p, q)].norm_sqr())
+        };
+        let total = a.inner.iter().fold(T::zero(), |acc, x| acc + x.norm_sqr());
+        let tol = T::epsilon() * T::epsilon() * total;
+
+        let mut sweeps = 0;
+        while off(&a) > tol {
+            if sweeps == MAX_SWEEPS {
+                return Err(QomputeTypeError::NonConvergent);
+            }
+            sweeps += 1;
+
+            for p in 0..n {
+                for q in p + 1..n {
+                    let apq = a[(p, q)];
+                    if apq.is_zero() {
+                        continue;
+                    }
+
+                    // Phase the pair so that the off-diagonal entry is real,
+                    // then zero it with a real rotation.
+                    let phase = apq / Complex::from(apq.norm());
+                    let theta = (apq.norm() * T::from(2).unwrap())
+                        .atan2(a[(q, q)].re - a[(p, p)].re)
+                        / T::from(2).unwrap();
+                    let (s, c) = theta.sin_cos();
+                    let (c, s) = (Complex::from(c), Complex::from(s));
+
+                    for k in 0..n {
+                        let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                        a[(k, p)] = akp * c - akq * s * phase.conj();
+                        a[(k, q)] = akp * s + akq * c * phase.conj();
+
+                        let (vkp, vkq) = (v[(k, p)], v[(k, q)]);
+                        v[(k, p)] = vkp * c - vkq * s * phase.conj();
+                        v[(k, q)] = vkp * s + vkq * c * phase.conj();
+                    }
+
+                    for k in 0..n {
+                        let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                        a[(p, k)] = apk * c - aqk * s * phase;
+                        a[(q, k)] = apk * s + aqk * c * phase;
+                    }
+
+                    a[(p, q)] = Complex::zero();
+                    a[(q, p)] = Complex::zero();
+                    a[(p, p)] = Complex::from(a[(p, p)].re);
+                    a[(q, q)] = Complex::from(a[(q, q)].re);
+                }
+            }
+        }
+
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| a[(i, i)].re.partial_cmp(&a[(j, j)].re).unwrap());
+
+        let values = order.iter().map(|&i| a[(i, i)].re).collect();
+        let vectors = order
+            .iter()
+            .map(|&j| (0..n).map(|i| v[(i, j)]).to_ket())
+            .collect();
+
+        Ok((values, vectors))
+    }
+
+    /// The eigenvalues of a Hermitian operator, in ascending order.
+    pub fn eigvalsh(&self) -> Result<Vec<T>, QomputeTypeError> {
+        self.eigh().map(|(values, _)| values)
+    }
+
+    /// The eigenvalues of a general square operator, found by reducing it to
+    /// Hessenberg form and running shifted QR iterations. They are sorted by
+    /// real part, then imaginary part.
+    pub fn eigvals(&self) -> Result<Vec<Complex<T>>, QomputeTypeError> {
+        check_square(self, "diagonalize")?;
+
+        if !super::norm_one(self).is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        let n = self.rows();
+        let mut h = hessenberg(self);
+        let mut values = Vec::with_capacity(n);
+
+        let mut hi = n;
+        let mut iterations = 0;
+
+        while hi > 0 {
+            let last = hi - 1;
+
+            let mut lo = last;
+            while lo > 0 {
+                let scale = h[(lo - 1, lo - 1)].norm() + h[(lo, lo)].norm();
+                if h[(lo, lo - 1)].norm() <= T::epsilon() * scale {
+                    h[(lo, lo - 1)] = Complex::zero();
+                    break;
+                }
+                lo -= 1;
+            }
+
+            if lo == last {
+                values.push(h[(last, last)]);
+                hi -= 1;
+                iterations = 0;
+                continue;
+            }
+
+            iterations += 1;
+            if iterations > MAX_QR_ITERATIONS * n {
+                return Err(QomputeTypeError::NonConvergent);
+            }
+
+            let shift = if iterations % 11 == 0 {
+                // An exceptional shift to break cycles.
+                h[(last, last)] + Complex::from(h[(last, last - 1)].norm())
+            } else {
+                wilkinson_shift(&h, last)
+            };
+
+            qr_step(&mut h, lo, last, shift);
+        }
+
+        values.sort_by(|a, b| {
+            a.re.partial_cmp(&b.re)
+                .unwrap()
+                .then(a.im.partial_cmp(&b.im).unwrap())
+        });
+
+        Ok(values)
+    }
+}
+
+/// Reduces a square operator to upper Hessenberg form with Householder
+/// reflections, preserving its eigenvalues.
+fn hessenberg<T: Float>(op: &Operator<T>) -> Operator<T> {
+    let n = op.rows();
+    let mut h = op.clone();
+
+    for k in 0..n.saturating_sub(2) {
+        let norm = (k + 1..n)
+            .fold(T::zero(), |acc, i| acc + h[(i, k)].norm_sqr())
+            .sqrt();
+        if norm.is_zero() {
+            continue;
+        }
+
+        let x0 = h[(k + 1, k)];
+        let phase = if x0.is_zero() {
+            Complex::one()
+        } else {
+            x0 / Complex::from(x0.norm())
+        };
+
+        let mut v = (k + 1..n).map(|i| h[(i, k)]).collect::<Vec<_>>();
+        v[0] = v[0] + phase * norm;
+        let v_norm = v.iter().fold(T::zero(), |acc, x| acc + x.norm_sqr()).sqrt();
+        v.iter_mut().for_each(|x| *x = *x / v_norm);
+
+        let two = Complex::from(T::from(2).unwrap());
+
+        // H <- (I - 2 v v^dagger) H
+        for j in 0..n {
+            let dot = v
+                .iter()
+                .enumerate()
+                .fold(Complex::zero(), |acc: Complex<T>, (i, x)| {
+                    acc + x.conj() * h[(k + 1 + i, j)]
+                });
+            for (i, x) in v.iter().enumerate() {
+                h[(k + 1 + i, j)] = h[(k + 1 + i, j)] - two * x * dot;
+            }
+        }
+
+        // H <- H (I - 2 v v^dagger)
+        for i in 0..n {
+            let dot = v
+                .iter()
+                .enumerate()
+                .fold(Complex::zero(), |acc: Complex<T>, (j, x)| {
+                    acc + h[(i, k + 1 + j)] * x
+                });
+            for (j, x) in v.iter().enumerate() {
+                h[(i, k + 1 + j)] = h[(i, k + 1 + j)] - two * dot * x.conj();
+            }
+        }
+    }
+
+    h
+}
+
+/// The eigenvalue of the trailing `2 x 2` block closest to its last entry.
+fn wilkinson_shift<T: Float>(h: &Operator<T>, last: usize) -> Complex<T> {
+    let (a, b) = (h[(last - 1, last - 1)], h[(last - 1, last)]);
+    let (c, d) = (h[(last, last - 1)], h[(last, last)]);
+
+    let half = Complex::from(T::from(0.5).unwrap());
+    let mean = (a + d) * half;
+    let disc = ((a - d) * (a - d) * half * half + b * c).sqrt();
+
+    let (mu1, mu2) = (mean + disc, mean - disc);
+    if (mu1 - d).norm() < (mu2 - d).norm() {
+        mu1
+    } else {
+        mu2
+    }
+}
+
+/// One shifted QR step `H - mu = QR`, `H <- RQ + mu` on the window
+/// `lo..=hi`, using Givens rotations.
+fn qr_step<T: Float>(h: &mut Operator<T>, lo: usize, hi: usize, shift: Complex<T>) {
+    for i in lo..=hi {
+        h[(i, i)] = h[(i, i)] - shift;
+    }
+
+    let mut rotations = Vec::with_capacity(hi - lo);
+
+    for k in lo..hi {
+        let (a, b) = (h[(k, k)], h[(k + 1, k)]);
+        let r = (a.norm_sqr() + b.norm_sqr()).sqrt();
+
+        let (c, s) = if r.is_zero() {
+            (Complex::one(), Complex::zero())
+        } else {
+            (a / Complex::from(r), b / Complex::from(r))
+        };
+
+        for j in k..=hi {
+            let (x, y) = (h[(k, j)], h[(k + 1, j)]);
+            h[(k, j)] = c.conj() * x + s.conj() * y;
+            h[(k + 1, j)] = c * y - s * x;
+        }
+
+        rotations.push((c, s));
+    }
+
+    for (k, (c, s)) in (lo..hi).zip(rotations) {
+        for i in lo..=(k + 1).min(hi) {
+            let (x, y) = (h[(i, k)], h[(i, k + 1)]);
+            h[(i, k)] = x * c + y * s;
+            h[(i, k + 1)] = y * c.conj() - x * s.conj();
+        }
+    }
+
+    for i in lo..=hi {
+        h[(i, i)] = h[(i, i)] + shift;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_eigh() {
+        let h = Operator::from([
+            [
+                Complex::new(2f64, 0.),
+                Complex::new(1., -1.),
+                Complex::new(0., 0.5),
+                Complex::new(0., 0.),
+            ],
+            [
+                Complex::new(1., 1.),
+                Complex::new(-1., 0.),
+                Complex::new(0., 0.),
+                Complex::new(3., 0.),
+            ],
+            [
+                Complex::new(0., -0.5),
+                Complex::new(0., 0.),
+                Complex::new(0., 0.),
+                Complex::new(1., 2.),
+            ],
+            [
+                Complex::new(0., 0.),
+                Complex::new(3., 0.),
+                Complex::new(1., -2.),
+                Complex::new(4., 0.),
+            ],
+        ]);
+
+        let (values, vectors) = h.eigh().unwrap();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert!(Operator::from([[1f64, 0.], [0., f64::INFINITY]])
+            .eigh()
+            .is_err());
+        assert!(Operator::from([[f64::NAN, 0.], [0., 1.]]).eigh().is_err());
+        assert!((values.iter().sum::<f64>() - 5.).abs() < 1e-12);
+
+        for (lambda, v) in values.iter().zip(&vectors) {
+            let hv = &h * v;
+            assert!(hv
+                .inner
+                .iter()
+                .zip(v.inner.iter())
+                .all(|(a, b)| (a - b * lambda).norm() < 1e-12));
+            assert!(((&v.dagger() * v).re - 1.).abs() < 1e-12);
+        }
+
+        assert_eq!(gates::y::<f64>().eigvalsh().unwrap(), [-1., 1.]);
+        assert!(Operator::from([[0f64, 1.], [0., 0.]]).eigh().is_err());
+    }
+
+    #[test]
+    fn test_eigh_rotated() {
+        let u = &(&gates::cnot::<f64>() * &(&gates::rx(0.7) & &gates::ry(-1.3)))
+            * &(&gates::u3(0.4, 1.1, -2.) & &gates::rz(0.9));
+        let d = Operator::from_diag([-2., -0.5, 1., 3.].into_iter().map(Complex::from));
+        let h = &(&u * &d) * &u.dagger();
+        assert!(!h.hermitian());
+
+        let (values, vectors) = h.eigh().unwrap();
+        assert!(values
+            .iter()
+            .zip([-2., -0.5, 1., 3.])
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+        for (lambda, v) in values.iter().zip(&vectors) {
+            assert!((&h * v)
+                .inner
+                .iter()
+                .zip(v.inner.iter())
+                .all(|(a, b)| (a - b * lambda).norm() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_eigvals() {
+        let close = |a: &[Complex<f64>], b: &[Complex<f64>]| {
+            a.len() == b.len() && b.iter().all(|b| a.iter().any(|a| (a - b).norm() < 1e-10))
+        };
+
+        let companion = Operator::from([[0f64, 1.], [-2., -3.]]);
+        assert!(close(
+            &companion.eigvals().unwrap(),
+            &[Complex::from(-2.), Complex::from(-1.)]
+        ));
+
+        let rotation = Operator::from([[0f64, -1.], [1., 0.]]);
+        assert!(close(
+            &rotation.eigvals().unwrap(),
+            &[-Complex::i(), Complex::i()]
+        ));
+
+        // The cyclic shift on 5 elements has the fifth roots of unity as its
+        // spectrum.
+        let mut shift = Operator::<f64>::from([[0.; 5]; 5]);
+        (0..5).for_each(|i| shift[((i + 1) % 5, i)] = Complex::from(1.));
+        let roots = (0..5)
+            .map(|k| Complex::from_polar(1., 2. * std::f64::consts::PI * k as f64 / 5.))
+            .collect::<Vec<_>>();
+        assert!(close(&shift.eigvals().unwrap(), &roots));
+
+        let h = gates::h::<f64>() & gates::z::<f64>();
+        let values = h.eigvals().unwrap();
+        assert!(close(&values, &[-1., -1., 1., 1.].map(Complex::from)));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::prelude::*;
+
+use super::{check_square, lu::Lu, norm_one};
+use crate::complex::braket::QomputeTypeError;
+
+/// Coefficients of the degree 13 Padé approximant to `e^x`.
+const PADE_13: [f64; 14] = [
+    64764752532480000.,
+    32382376266240000.,
+    7771770303897600.,
+    1187353796428800.,
+    129060195264000.,
+    10559470521600.,
+    670442572800.,
+    33522128640.,
+    1323241920.,
+    40840800.,
+    960960.,
+    16380.,
+    182.,
+    1.,
+];
+
+/// The largest 1-norm for which the degree 13 approximant is accurate to
+/// double precision.
+const THETA_13: f64 = 5.371920351148152;
+
+/// Pairs `(m, theta_m)` such that a degree `m` Taylor polynomial gives
+/// `e^A` to double precision whenever `A` has 1-norm at most `theta_m`
+/// (Al-Mohy and Higham, 2011, Table 3.1).
+const THETA_TAYLOR: [(usize, f64); 35] = [
+    (1, 2.29e-16),
+    (2, 2.58e-8),
+    (3, 1.39e-5),
+    (4, 3.40e-4),
+    (5, 2.40e-3),
+    (6, 9.07e-3),
+    (7, 2.38e-2),
+    (8, 5.00e-2),
+    (9, 8.96e-2),
+    (10, 1.44e-1),
+    (11, 2.14e-1),
+    (12, 3.00e-1),
+    (13, 4.00e-1),
+    (14, 5.14e-1),
+    (15, 6.41e-1),
+    (16, 7.81e-1),
+    (17, 9.31e-1),
+    (18, 1.09),
+    (19, 1.26),
+    (20, 1.44),
+    (21, 1.62),
+    (22, 1.82),
+    (23, 2.01),
+    (24, 2.22),
+    (25, 2.43),
+    (26, 2.64),
+    (27, 2.86),
+    (28, 3.08),
+    (29, 3.31),
+    (30, 3.54),
+    (35, 4.7),
+    (40, 6.0),
+    (45, 7.2),
+    (50, 8.5),
+    (55, 9.9),
+];
+
+/// The most steps `expm_multiply` will take, bounding its run time to about
+/// `55 * MAX_STEPS` matrix-vector products.
+const MAX_STEPS: usize = 1 << 20;
+
+fn max_abs<T: Float>(ket: &Ket<T>) -> T {
+    ket.inner.iter().fold(T::zero(), |m, a| m.max(a.norm()))
+}
+
+impl<T: Float> Operator<T> {
+    /// The matrix exponential `e^A`, by a degree 13 Padé approximant with
+    /// scaling and squaring (Higham, 2005).
+    pub fn expm(&self) -> Result<Operator<T>, QomputeTypeError> {
+        check_square(self, "exponentiate")?;
+
+        let norm = norm_one(self).to_f64().unwrap();
+        if !norm.is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        let squarings = if norm > THETA_13 {
+            (norm / THETA_13).log2().ceil() as i32
+        } else {
+            0
+        };
+
+        let a = self * Complex::from(T::from(2f64.powi(-squarings)).unwrap());
+        let b = |k: usize| Complex::from(T::from(PADE_13[k]).unwrap());
+        let ident = Operator::new_with_shape(self.shape());
+
+        let a2 = &a * &a;
+        let a4 = &a2 * &a2;
+        let a6 = &a2 * &a4;
+
+        let u = &a6 * (&a6 * b(13) + &a4 * b(11) + &a2 * b(9))
+            + &a6 * b(7)
+            + &a4 * b(5)
+            + &a2 * b(3)
+            + &ident * b(1);
+        let u = &a * u;
+        let v = &a6 * (&a6 * b(12) + &a4 * b(10) + &a2 * b(8))
+            + &a6 * b(6)
+            + &a4 * b(4)
+            + &a2 * b(2)
+            + &ident * b(0);
+
+        let mut exp = Lu::new(&(&v - &u))
+            .solve(&(&v + &u))
+            .ok_or(QomputeTypeError::NonFinite)?;
+
+        for _ in 0..squarings {
+            exp = &exp * &exp;
+        }
+
+        Ok(exp)
+    }
+
+    /// Computes `e^A |ket>` from matrix-vector products alone, as `s` steps
+    /// of a degree `m` Taylor polynomial in `A / s` (Al-Mohy and Higham,
+    /// 2011). The pair `(m, s)` minimizes the cost `m * s` subject to
+    /// `||A||_1 / s <= theta_m`, bounding `||A^p||^(1/p)` by `||A||_1`
+    /// rather than estimating it, and each step stops early once the terms
+    /// become negligible.
+    ///
+    /// The number of steps grows linearly with `||A||_1`, so operators with
+    /// a norm above `theta_55 * 2^20` (about `10^7`) are rejected with
+    /// [`QomputeTypeError::Overflow`].
+    pub fn expm_multiply(&self, ket: &Ket<T>) -> Result<Ket<T>, QomputeTypeError> {
+        check_square(self, "exponentiate")?;
+        crate::complex::ops::check_shape("apply", (self.cols(), 1).into(), ket.shape())?;
+
+        let norm = norm_one(self).to_f64().unwrap();
+        if !norm.is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        let (degree, steps) = THETA_TAYLOR
+            .iter()
+            .filter_map(|&(m, theta)| {
+                let s = (norm / theta).ceil().max(1.);
+                (s <= MAX_STEPS as f64).then_some((m, s as usize))
+            })
+            .min_by_key(|&(m, s)| m * s)
+            .ok_or(QomputeTypeError::Overflow("exponentiate"))?;
+        let scale = Complex::from(T::from(steps).unwrap().recip());
+
+        let mut out = ket.clone();
+        let mut term = ket.clone();
+        let mut next = ket.clone();
+
+        for _ in 0..steps {
+            term.inner.copy_from_slice(&out.inner);
+
+            for k in 1..=degree {
+                self.apply_into(&term, &mut next);
+                next *= scale / T::from(k).unwrap();
+                std::mem::swap(&mut term, &mut next);
+                out += &term;
+
+                if max_abs(&term) <= T::epsilon() * max_abs(&out) {
+                    break;
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// The propagator `e^{-i H t}` of a time-independent Hamiltonian, which need
+/// only be Hermitian up to rounding.
+pub fn evolve<T: Float>(hamiltonian: &Operator<T>, t: T) -> Result<Operator<T>, QomputeTypeError> {
+    check_square(hamiltonian, "exponentiate")?;
+
+    let hamiltonian = super::hermitian_part(hamiltonian)?;
+
+    (&hamiltonian * Complex::new(T::zero(), -t)).expm()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    fn assert_close(lhs: &Operator<f64>, rhs: &Operator<f64>, tol: f64) {
+        assert_eq!(lhs.shape(), rhs.shape());
+        assert!(lhs
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a - b).norm() < tol));
+    }
+
+    #[test]
+    fn test_expm() {
+        let x = gates::x::<f64>();
+
+        for theta in [0.3, 4., 100.] {
+            let u = linalg::evolve(&(&x * Complex::from(0.5)), theta).unwrap();
+            assert_close(&u, &gates::rx(theta), 1e-12);
+        }
+
+        let h = Operator::from([[1f64, 2.], [2., -3.]]);
+        let u = linalg::evolve(&h, 2.5).unwrap();
+        assert_close(&(&u * u.dagger()), &gates::i(), 1e-12);
+        assert_close(&(&u * &u), &linalg::evolve(&h, 5.).unwrap(), 1e-10);
+
+        let diag = Operator::from_diag([Complex::new(1f64, 2.), Complex::new(-20., 0.)]);
+        assert_close(
+            &diag.expm().unwrap(),
+            &Operator::from_diag([Complex::new(1f64, 2.).exp(), Complex::from((-20f64).exp())]),
+            1e-12,
+        );
+
+        assert_eq!(
+            linalg::evolve(&Operator::from([[0f64, 1.], [0., 0.]]), 1.),
+            Err(QomputeTypeError::NonHermitian)
+        );
+        assert!(Operator::from([[1f64, 2.]]).expm().is_err());
+
+        // Hermitian only up to rounding.
+        let v = &gates::cnot::<f64>() * &(&gates::rx(0.7) & &gates::ry(-1.3));
+        let d = [-2., -0.5, 1., 3.];
+        let h = &(&v * &Operator::from_diag(d.into_iter().map(Complex::from))) * &v.dagger();
+        assert!(!h.hermitian());
+        let phases = Operator::from_diag(d.into_iter().map(|x| Complex::from_polar(1., -1.5 * x)));
+        assert_close(
+            &linalg::evolve(&h, 1.5).unwrap(),
+            &(&(&v * &phases) * &v.dagger()),
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn test_expm_multiply() {
+        let a = Operator::from([
+            [
+                Complex::new(0.5f64, -1.),
+                Complex::new(2., 0.),
+                Complex::new(0., 1.),
+            ],
+            [
+                Complex::new(-3., 0.),
+                Complex::new(0., 0.),
+                Complex::new(1., 1.),
+            ],
+            [
+                Complex::new(0., 0.),
+                Complex::new(4., -2.),
+                Complex::new(-1., 0.),
+            ],
+        ]);
+        let ket = Ket::from([1f64, -2., 0.5].as_slice());
+
+        let expected = &a.expm().unwrap() * &ket;
+        let out = a.expm_multiply(&ket).unwrap();
+
+        assert!(out
+            .inner
+            .iter()
+            .zip(expected.inner.iter())
+            .all(|(a, b)| (a - b).norm() < 1e-10 * b.norm().max(1.)));
+
+        let rotation = &gates::x::<f64>() * Complex::new(0., -40.);
+        let out = rotation
+            .expm_multiply(&Ket::from([1f64, 0.].as_slice()))
+            .unwrap();
+        assert!((out[0] - Complex::from(40f64.cos())).norm() < 1e-10);
+        assert!((out[1] - Complex::new(0., -(40f64.sin()))).norm() < 1e-10);
+
+        let huge = Operator::from([[1e300f64, 0.], [0., 0.]]);
+        assert_eq!(
+            huge.expm_multiply(&Ket::from([1f64, 0.].as_slice())),
+            Err(QomputeTypeError::Overflow("exponentiate"))
+        );
+    }
+}
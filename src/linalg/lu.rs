@@ -0,0 +1,104 @@
+use crate::prelude::*;
+
+use num::{One, Zero};
+
+/// An `LU` factorization with partial pivoting, `P A = L U`, stored packed
+/// with the unit diagonal of `L` implied.
+pub(crate) struct Lu<T: Float> {
+    lu: Operator<T>,
+    perm: Vec<usize>,
+    odd: bool,
+    singular: bool,
+}
+
+impl<T: Float> Lu<T> {
+    /// Factors a square operator.
+    pub(crate) fn new(op: &Operator<T>) -> Self {
+        let n = op.rows();
+        let mut lu = op.clone();
+        let mut perm = (0..n).collect::<Vec<_>>();
+        let mut odd = false;
+        let mut singular = false;
+
+        for k in 0..n {
+            // NaN moduli never compare greater, so they are never chosen as
+            // pivots ahead of a number.
+            let pivot = (k + 1..n).fold(k, |best, i| {
+                if lu[(i, k)].norm() > lu[(best, k)].norm() {
+                    i
+                } else {
+                    best
+                }
+            });
+
+            if pivot != k {
+                for j in 0..n {
+                    lu.inner.swap(k * n + j, pivot * n + j);
+                }
+                perm.swap(k, pivot);
+                odd = !odd;
+            }
+
+            let diag = lu[(k, k)];
+            if diag.is_zero() {
+                singular = true;
+                continue;
+            }
+
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / diag;
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    let u = lu[(k, j)];
+                    lu[(i, j)] = lu[(i, j)] - factor * u;
+                }
+            }
+        }
+
+        Self {
+            lu,
+            perm,
+            odd,
+            singular,
+        }
+    }
+
+    pub(crate) fn det(&self) -> Complex<T> {
+        let det = (0..self.lu.rows()).fold(Complex::one(), |acc, i| acc * self.lu[(i, i)]);
+        if self.odd {
+            -det
+        } else {
+            det
+        }
+    }
+
+    /// Solves `A X = B`, or returns `None` if `A` is singular.
+    pub(crate) fn solve(&self, b: &Operator<T>) -> Option<Operator<T>> {
+        if self.singular {
+            return None;
+        }
+
+        let n = self.lu.rows();
+        let cols = b.cols();
+        let mut x = Operator::new_with_shape((n, cols).into());
+
+        for (i, &p) in self.perm.iter().enumerate() {
+            for j in 0..cols {
+                x[(i, j)] = b[(p, j)];
+            }
+        }
+
+        for j in 0..cols {
+            for i in 0..n {
+                let sum = (0..i).fold(x[(i, j)], |acc, k| acc - self.lu[(i, k)] * x[(k, j)]);
+                x[(i, j)] = sum;
+            }
+            for i in (0..n).rev() {
+                let sum = (i + 1..n).fold(x[(i, j)], |acc, k| acc - self.lu[(i, k)] * x[(k, j)]);
+                x[(i, j)] = sum / self.lu[(i, i)];
+            }
+        }
+
+        Some(x)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::prelude::*;
+
+use super::{check_square, lu::Lu, norm_one};
+use crate::complex::braket::QomputeTypeError;
+
+/// The largest entrywise distance between two operators of the same shape.
+fn max_abs_diff<T: Float>(lhs: &Operator<T>, rhs: &Operator<T>) -> T {
+    lhs.inner
+        .iter()
+        .zip(rhs.inner.iter())
+        .fold(T::zero(), |m, (a, b)| m.max((a - b).norm()))
+}
+
+impl<T: Float> Operator<T> {
+    /// The determinant, from an LU factorization with partial pivoting.
+    /// Fails for a non-square operator or one with non-finite entries.
+    pub fn det(&self) -> Result<Complex<T>, QomputeTypeError> {
+        check_square(self, "take the determinant")?;
+
+        if !norm_one(self).is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        Ok(Lu::new(self).det())
+    }
+
+    /// The inverse, from an LU factorization with partial pivoting.
+    ///
+    /// Only an exactly zero pivot is reported as
+    /// [`QomputeTypeError::Singular`]. There is no conditioning tolerance, so
+    /// a numerically singular operator may return an inverse with huge or
+    /// non-finite entries; check [`Operator::singular_values`] first if that
+    /// matters.
+    pub fn inverse(&self) -> Result<Operator<T>, QomputeTypeError> {
+        check_square(self, "invert")?;
+
+        if !norm_one(self).is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        Lu::new(self)
+            .solve(&Operator::new_with_shape(self.shape()))
+            .ok_or(QomputeTypeError::Singular)
+    }
+
+    /// The square root of the sum of squared moduli of all entries.
+    pub fn frobenius_norm(&self) -> T {
+        self.inner
+            .iter()
+            .fold(T::zero(), |acc, a| acc + a.norm_sqr())
+            .sqrt()
+    }
+
+    /// The largest singular value.
+    pub fn operator_norm(&self) -> Result<T, QomputeTypeError> {
+        Ok(self
+            .singular_values()?
+            .first()
+            .copied()
+            .unwrap_or_else(T::zero))
+    }
+
+    /// The sum of the singular values.
+    pub fn trace_norm(&self) -> Result<T, QomputeTypeError> {
+        Ok(self
+            .singular_values()?
+            .into_iter()
+            .fold(T::zero(), |a, b| a + b))
+    }
+
+    /// Whether `U^dagger U` is within `tol` of the identity in every entry.
+    pub fn is_unitary(&self, tol: T) -> bool {
+        self.rows() == self.cols()
+            && max_abs_diff(
+                &(&self.dagger() * self),
+                &Operator::new_with_shape(self.shape()),
+            ) <= tol
+    }
+
+    /// Whether `A` commutes with `A^dagger` up to `tol` in every entry.
+    pub fn is_normal(&self, tol: T) -> bool {
+        self.rows() == self.cols() && {
+            let dagger = self.dagger();
+            max_abs_diff(&(self * &dagger), &(&dagger * self)) <= tol
+        }
+    }
+
+    /// Whether every off-diagonal entry is within `tol` of zero.
+    pub fn is_diagonal(&self, tol: T) -> bool {
+        (0..self.rows())
+            .flat_map(|i| (0..self.cols()).map(move |j| (i, j)))
+            .all(|(i, j)| i == j || self[(i, j)].norm() <= tol)
+    }
+
+    /// `[A, B] = AB - BA`.
+    pub fn commutator(&self, rhs: &Operator<T>) -> Result<Operator<T>, QomputeTypeError> {
+        self.checked_mul(rhs)?.checked_sub(&rhs.checked_mul(self)?)
+    }
+
+    /// `{A, B} = AB + BA`.
+    pub fn anticommutator(&self, rhs: &Operator<T>) -> Result<Operator<T>, QomputeTypeError> {
+        self.checked_mul(rhs)?.checked_add(&rhs.checked_mul(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_inverse_and_det() {
+        let a = Operator::from([
+            [
+                Complex::new(0f64, 0.),
+                Complex::new(2., 1.),
+                Complex::new(1., 0.),
+            ],
+            [
+                Complex::new(1., -1.),
+                Complex::new(0., 0.),
+                Complex::new(3., 0.),
+            ],
+            [
+                Complex::new(2., 0.),
+                Complex::new(1., 0.),
+                Complex::new(0., 1.),
+            ],
+        ]);
+
+        let det = a.det().unwrap();
+        assert!((det - Complex::new(12., 2.)).norm() < 1e-12);
+
+        let inv = a.inverse().unwrap();
+        assert!((&a * &inv).is_unitary(1e-12) && (&a * &inv).is_diagonal(1e-12));
+        assert!(((&a * &inv).trace() - Complex::from(3.)).norm() < 1e-12);
+        assert!((inv.det().unwrap() * det - Complex::from(1.)).norm() < 1e-12);
+
+        let singular = Operator::from([[1f64, 2.], [2., 4.]]);
+        assert_eq!(singular.det().unwrap(), Complex::from(0.));
+        assert!(singular.inverse().is_err());
+        assert!(Operator::from([[1f64, 2.]]).det().is_err());
+
+        let nan = Operator::from([[f64::NAN, 1.], [1., 0.]]);
+        assert_eq!(nan.det(), Err(QomputeTypeError::NonFinite));
+        assert_eq!(nan.inverse(), Err(QomputeTypeError::NonFinite));
+    }
+
+    #[test]
+    fn test_norms_and_commutators() {
+        use gates::*;
+
+        let a = Operator::from([[3f64, 0.], [4., 0.]]);
+        assert_eq!(a.frobenius_norm(), 5.);
+        assert!((a.operator_norm().unwrap() - 5.).abs() < 1e-12);
+        assert!((x::<f64>().trace_norm().unwrap() - 2.).abs() < 1e-12);
+
+        assert!(h::<f64>().is_unitary(1e-12) && h::<f64>().is_normal(1e-12));
+        assert!(!a.is_unitary(1e-12) && !a.is_normal(1e-12));
+        assert!(!Operator::from([[1f64, 0.]]).is_unitary(1.));
+        assert!(s::<f64>().is_diagonal(0.) && !h::<f64>().is_diagonal(0.5));
+
+        let xy = x::<f64>().commutator(&y()).unwrap();
+        assert_eq!(xy, z() * Complex::new(0., 2.));
+        assert_eq!(
+            x::<f64>().anticommutator(&y()).unwrap(),
+            Operator::from([[0f64; 2]; 2])
+        );
+        assert!(x::<f64>().commutator(&cnot()).is_err());
+    }
+}
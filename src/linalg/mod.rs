@@ -0,0 +1,64 @@
+mod eig;
+mod expm;
+pub(crate) mod lu;
+mod matrix;
+mod svd;
+
+pub use expm::evolve;
+pub use svd::{Schmidt, Svd};
+
+use crate::prelude::*;
+
+use crate::complex::braket::QomputeTypeError;
+
+pub(crate) fn check_square<T: Float>(
+    op: &Operator<T>,
+    name: &'static str,
+) -> Result<(), QomputeTypeError> {
+    if op.rows() == op.cols() {
+        Ok(())
+    } else {
+        Err(QomputeTypeError::ShapeMismatch {
+            op: name,
+            expected: (op.rows(), op.rows()).into(),
+            found: op.shape(),
+        })
+    }
+}
+
+/// The Hermitian part `(A + A^dagger) / 2` of an operator that is Hermitian
+/// up to rounding, meaning `max |A - A^dagger| <= 16 n eps ||A||_1`. Products
+/// like `U D U^dagger` are rarely Hermitian to the last bit, and symmetrizing
+/// them keeps that error out of the result.
+pub(crate) fn hermitian_part<T: Float>(op: &Operator<T>) -> Result<Operator<T>, QomputeTypeError> {
+    let norm = norm_one(op);
+    if !norm.is_finite() {
+        return Err(QomputeTypeError::NonFinite);
+    }
+
+    let n = op.rows();
+    let tol = T::from(16 * n).unwrap() * T::epsilon() * norm;
+    let half = Complex::from(T::from(0.5).unwrap());
+
+    let mut out = op.clone();
+    for i in 0..n {
+        for j in i..n {
+            let (a, b) = (op[(i, j)], op[(j, i)].conj());
+            if (a - b).norm() > tol {
+                return Err(QomputeTypeError::NonHermitian);
+            }
+
+            out[(i, j)] = (a + b) * half;
+            out[(j, i)] = out[(i, j)].conj();
+        }
+    }
+
+    Ok(out)
+}
+
+/// The maximum absolute column sum, or NaN if any entry is NaN.
+pub(crate) fn norm_one<T: Float>(op: &Operator<T>) -> T {
+    (0..op.cols())
+        .map(|j| (0..op.rows()).fold(T::zero(), |acc, i| acc + op[(i, j)].norm()))
+        .fold(T::zero(), |m, c| if c.is_nan() || c > m { c } else { m })
+}
@@ -0,0 +1,255 @@
+use crate::prelude::*;
+
+use num::Zero;
+
+use super::norm_one;
+use crate::complex::braket::QomputeTypeError;
+use crate::complex::ops::check_shape;
+
+const MAX_SWEEPS: usize = 64;
+
+/// `(U, singular values, V^dagger)`.
+pub type Svd<T> = (Operator<T>, Vec<T>, Operator<T>);
+
+/// `(coefficients, kets of the first party, kets of the second party)`.
+pub type Schmidt<T> = (Vec<T>, Vec<Ket<T>>, Vec<Ket<T>>);
+
+/// One-sided Jacobi SVD of an `m x n` operator with `m >= n`, returning the
+/// unnormalized left vectors `A V` alongside `V`.
+fn jacobi<T: Float>(op: &Operator<T>) -> Result<(Operator<T>, Operator<T>), QomputeTypeError> {
+    let n = op.cols();
+    let mut w = op.clone();
+    let mut v = Operator::new_with_shape((n, n).into());
+
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let (mut alpha, mut beta, mut gamma) = (T::zero(), T::zero(), Complex::<T>::zero());
+                for i in 0..w.rows() {
+                    let (wp, wq) = (w[(i, p)], w[(i, q)]);
+                    alpha = alpha + wp.norm_sqr();
+                    beta = beta + wq.norm_sqr();
+                    gamma = gamma + wp.conj() * wq;
+                }
+
+                if gamma.norm() <= T::epsilon() * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+
+                // Rotate the pair so that columns `p` and `q` become orthogonal.
+                let phase = gamma / Complex::from(gamma.norm());
+                let zeta = (beta - alpha) / (gamma.norm() * T::from(2).unwrap());
+                let t = zeta.signum() / (zeta.abs() + (T::one() + zeta * zeta).sqrt());
+                let c = T::one() / (T::one() + t * t).sqrt();
+                let (c, s) = (Complex::from(c), Complex::from(c * t));
+
+                for m in [&mut w, &mut v] {
+                    for i in 0..m.rows() {
+                        let (xp, xq) = (m[(i, p)], m[(i, q)]);
+                        m[(i, p)] = xp * c - xq * s * phase.conj();
+                        m[(i, q)] = xp * s * phase + xq * c;
+                    }
+                }
+            }
+        }
+
+        if !rotated {
+            return Ok((w, v));
+        }
+    }
+
+    Err(QomputeTypeError::NonConvergent)
+}
+
+/// Fills the columns of `u` listed in `missing` with unit vectors orthogonal
+/// to every other column, by Gram-Schmidt against the standard basis.
+fn complete_basis<T: Float>(u: &mut Operator<T>, missing: &[usize]) {
+    let (m, k) = u.shape().into();
+    let mut basis = (0..m).map(|i| {
+        let mut e = vec![Complex::zero(); m];
+        e[i] = Complex::from(T::one());
+        e
+    });
+
+    for &j in missing {
+        for mut e in basis.by_ref() {
+            // Columns still to be filled are zero and can be skipped.
+            for l in (0..k).filter(|&l| l != j && (l < j || !missing.contains(&l))) {
+                let dot = (0..m).fold(Complex::zero(), |acc: Complex<T>, i| {
+                    acc + u[(i, l)].conj() * e[i]
+                });
+                (0..m).for_each(|i| e[i] = e[i] - u[(i, l)] * dot);
+            }
+
+            let norm = e.iter().fold(T::zero(), |acc, x| acc + x.norm_sqr()).sqrt();
+            if norm > T::from(0.5).unwrap() {
+                (0..m).for_each(|i| u[(i, j)] = e[i] / norm);
+                break;
+            }
+        }
+    }
+}
+
+impl<T: Float> Operator<T> {
+    /// The thin singular value decomposition `A = U S V^dagger`, with `U` of
+    /// shape `m x k`, `V^dagger` of shape `k x n` for `k = min(m, n)`, and the
+    /// singular values in descending order.
+    pub fn svd(&self) -> Result<Svd<T>, QomputeTypeError> {
+        if !norm_one(self).is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+
+        if self.rows() < self.cols() {
+            let (u, s, vt) = self.dagger().svd()?;
+            return Ok((vt.dagger(), s, u.dagger()));
+        }
+
+        let (w, v) = jacobi(self)?;
+        let (m, n) = self.shape().into();
+
+        let norms = (0..n)
+            .map(|j| {
+                (0..m)
+                    .fold(T::zero(), |acc, i| acc + w[(i, j)].norm_sqr())
+                    .sqrt()
+            })
+            .collect::<Vec<_>>();
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| norms[j].partial_cmp(&norms[i]).unwrap());
+
+        let tol =
+            norms.iter().fold(T::zero(), |a, &b| a.max(b)) * T::epsilon() * T::from(m).unwrap();
+
+        let mut u = Operator::new_with_shape((m, n).into());
+        let mut vt = Operator::new_with_shape((n, n).into());
+        let mut missing = Vec::new();
+
+        for (k, &j) in order.iter().enumerate() {
+            for i in 0..m {
+                u[(i, k)] = if norms[j] > tol {
+                    w[(i, j)] / norms[j]
+                } else {
+                    Complex::zero()
+                };
+            }
+            if norms[j] <= tol {
+                missing.push(k);
+            }
+            for i in 0..n {
+                vt[(k, i)] = v[(i, j)].conj();
+            }
+        }
+        complete_basis(&mut u, &missing);
+
+        Ok((u, order.iter().map(|&j| norms[j]).collect(), vt))
+    }
+
+    /// The singular values in descending order.
+    pub fn singular_values(&self) -> Result<Vec<T>, QomputeTypeError> {
+        self.svd().map(|(_, s, _)| s)
+    }
+}
+
+impl<T: Float> Ket<T> {
+    /// Splits a state on `dims_a * dims_b` entries, laid out as by
+    /// `tensorprod`, into `sum_k c_k |a_k> (x) |b_k>` with orthonormal local
+    /// kets. The `min(dims_a, dims_b)` coefficients are in descending order,
+    /// so truncating a bond amounts to keeping a prefix.
+    pub fn schmidt(&self, dims_a: usize, dims_b: usize) -> Result<Schmidt<T>, QomputeTypeError> {
+        let size = dims_a
+            .checked_mul(dims_b)
+            .ok_or(QomputeTypeError::Overflow("split the state"))?;
+        check_shape("split the state", (size, 1).into(), self.shape())?;
+
+        let m = Operator {
+            shape: (dims_a, dims_b).into(),
+            inner: self.inner.iter().copied().collect(),
+        };
+        let (u, s, vt) = m.svd()?;
+
+        let a = (0..s.len())
+            .map(|k| (0..dims_a).map(|i| u[(i, k)]).to_ket())
+            .collect();
+        let b = (0..s.len())
+            .map(|k| (0..dims_b).map(|j| vt[(k, j)]).to_ket())
+            .collect();
+
+        Ok((s, a, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn assert_close(lhs: &Operator<f64>, rhs: &Operator<f64>) {
+        assert_eq!(lhs.shape(), rhs.shape());
+        assert!(lhs
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+    }
+
+    #[test]
+    fn test_svd() {
+        let a = Operator::from([
+            [
+                Complex::new(1f64, 0.),
+                Complex::new(2., 1.),
+                Complex::new(0., -1.),
+            ],
+            [
+                Complex::new(0., 3.),
+                Complex::new(-1., 0.),
+                Complex::new(2., 2.),
+            ],
+        ]);
+
+        for op in [a.clone(), a.dagger()] {
+            let (u, s, vt) = op.svd().unwrap();
+            assert!(s.windows(2).all(|w| w[0] >= w[1]));
+
+            let sigma = Operator::from_diag(s.iter().map(|&x| Complex::from(x)));
+            assert_close(&(&(&u * &sigma) * &vt), &op);
+            assert_close(
+                &(&u.dagger() * &u),
+                &Operator::new_with_shape((2, 2).into()),
+            );
+            assert_close(
+                &(&vt * &vt.dagger()),
+                &Operator::new_with_shape((2, 2).into()),
+            );
+        }
+
+        // Rank deficient operators still get an orthonormal `U`.
+        let (u, s, _) = Operator::from([[1f64, 1.], [1., 1.]]).svd().unwrap();
+        assert!((s[0] - 2.).abs() < 1e-12 && s[1].abs() < 1e-12);
+        assert_close(
+            &(&u.dagger() * &u),
+            &Operator::new_with_shape((2, 2).into()),
+        );
+    }
+
+    #[test]
+    fn test_schmidt() {
+        let amp = 0.5f64.sqrt();
+        let (s, _, _) = Ket::from([amp, 0., 0., amp].as_slice())
+            .schmidt(2, 2)
+            .unwrap();
+        assert!(s.iter().all(|s| (s - amp).abs() < 1e-12));
+
+        let a = Ket::from([0.6f64, 0.8].as_slice());
+        let b = Ket::from([Complex::new(0., 1.), Complex::from(0.), Complex::from(0.)]);
+        let (s, ka, kb) = a.tensorprod(&b).schmidt(2, 3).unwrap();
+        assert!((s[0] - 1.).abs() < 1e-12 && s[1].abs() < 1e-12);
+
+        let rebuilt = ka[0].tensorprod(&kb[0]);
+        assert!((&a.tensorprod(&b).dagger() * &rebuilt).norm() > 1. - 1e-12);
+
+        assert!(a.schmidt(2, 2).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::prelude::*;
+
+use num::Zero;
+use rand::Rng;
+
+use crate::complex::braket::QomputeTypeError;
+use crate::state::density::{conjugate, tolerance};
+
+/// A quantum channel `rho -> sum_i K_i rho K_i^\dagger` given by its Kraus
+/// operators, which all act on the same `k` qubits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel<T: Float> {
+    kraus: Vec<Operator<T>>,
+}
+
+impl<T: Float> Channel<T> {
+    /// Builds a channel after checking that `sum_i K_i^\dagger K_i = I`.
+    pub fn new(kraus: Vec<Operator<T>>) -> Result<Self, QomputeTypeError> {
+        let first = kraus.first().ok_or(QomputeTypeError::EmptyInput)?;
+        let shape = first.shape();
+
+        if shape.rows != shape.cols || !shape.rows.is_power_of_two() {
+            return Err(QomputeTypeError::NonQubitDimension(shape.rows));
+        }
+
+        if kraus.iter().any(|k| k.shape() != shape) {
+            return Err(QomputeTypeError::NonMatchingSizes);
+        }
+
+        let sum = kraus.iter().map(|k| k.dagger() * k).fold(
+            Operator::new_with_shape(shape) * Complex::<T>::zero(),
+            |a, b| a + b,
+        );
+        let identity = Operator::new_with_shape(shape);
+        let tol = tolerance::<T>(shape.rows);
+
+        if !(sum - identity).inner.iter().all(|a| a.norm() <= tol) {
+            return Err(QomputeTypeError::NonTracePreserving);
+        }
+
+        Ok(Self { kraus })
+    }
+
+    /// Builds a channel from weighted operators `sqrt(w_i) O_i`, dropping
+    /// those with zero weight.
+    fn weighted<const N: usize>(terms: [(T, Operator<T>); N]) -> Result<Self, QomputeTypeError> {
+        if terms.iter().any(|(w, _)| w.is_nan() || *w < T::zero()) {
+            return Err(QomputeTypeError::NonTracePreserving);
+        }
+
+        Self::new(
+            terms
+                .into_iter()
+                .filter(|(w, _)| !w.is_zero())
+                .map(|(w, op)| op * Complex::from(w.sqrt()))
+                .collect(),
+        )
+    }
+
+    /// Applies `X`, `Y` and `Z` with probabilities `px`, `py` and `pz`.
+    pub fn pauli(px: T, py: T, pz: T) -> Result<Self, QomputeTypeError> {
+        let (o, l) = (T::zero(), T::one());
+        let (zero, i) = (Complex::<T>::zero(), Complex::<T>::i());
+
+        Self::weighted([
+            (l - px - py - pz, Operator::from([[l, o], [o, l]])),
+            (px, Operator::from([[o, l], [l, o]])),
+            (py, Operator::from([[zero, -i], [i, zero]])),
+            (pz, Operator::from([[l, o], [o, -l]])),
+        ])
+    }
+
+    /// `rho -> (1 - p) rho + p I / 2`.
+    pub fn depolarizing(p: T) -> Result<Self, QomputeTypeError> {
+        let quarter = p / T::from(4).unwrap();
+        Self::pauli(quarter, quarter, quarter)
+    }
+
+    pub fn bit_flip(p: T) -> Result<Self, QomputeTypeError> {
+        Self::pauli(p, T::zero(), T::zero())
+    }
+
+    pub fn phase_flip(p: T) -> Result<Self, QomputeTypeError> {
+        Self::pauli(T::zero(), T::zero(), p)
+    }
+
+    /// Decay of `|1>` into `|0>` with probability `gamma`.
+    pub fn amplitude_damping(gamma: T) -> Result<Self, QomputeTypeError> {
+        let (o, l) = (T::zero(), T::one());
+
+        Self::weighted([
+            (l, Operator::from([[l, o], [o, (l - gamma).sqrt()]])),
+            (gamma, Operator::from([[o, l], [o, o]])),
+        ])
+    }
+
+    /// Loss of coherence between `|0>` and `|1>` with probability `lambda`,
+    /// without any energy loss.
+    pub fn phase_damping(lambda: T) -> Result<Self, QomputeTypeError> {
+        let (o, l) = (T::zero(), T::one());
+
+        Self::weighted([
+            (l, Operator::from([[l, o], [o, (l - lambda).sqrt()]])),
+            (lambda, Operator::from([[o, o], [o, l]])),
+        ])
+    }
+
+    pub fn kraus(&self) -> &[Operator<T>] {
+        &self.kraus
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.kraus[0].rows().trailing_zeros() as usize
+    }
+
+    /// Applies the channel to the given qubits of `rho`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`DensityMatrix::apply`].
+    pub fn apply_density(&self, rho: &mut DensityMatrix<T>, qubits: &[usize]) {
+        let shape = rho.rho.shape();
+
+        rho.rho = self
+            .kraus
+            .iter()
+            .map(|k| conjugate(&rho.rho, k, qubits))
+            .fold(
+                Operator::new_with_shape(shape) * Complex::<T>::zero(),
+                |a, b| a + b,
+            );
+    }
+
+    /// Applies a single Kraus operator to the given qubits of `state`, chosen
+    /// with probability `||K_i |psi>||^2`, and renormalizes. Averaging over
+    /// many such trajectories reproduces [`Channel::apply_density`].
+    ///
+    /// Returns the index of the Kraus operator that was applied.
+    pub fn apply_trajectory<R: Rng + ?Sized>(
+        &self,
+        state: &mut StateVector<T>,
+        qubits: &[usize],
+        rng: &mut R,
+    ) -> usize {
+        let norm_sqr = |s: &StateVector<T>| {
+            s.amplitudes()
+                .iter()
+                .fold(T::zero(), |acc, a| acc + a.norm_sqr())
+        };
+
+        let total = norm_sqr(state);
+        let r = T::from(rng.gen::<f64>()).unwrap() * total;
+        let mut acc = T::zero();
+        let mut chosen = None;
+
+        for (idx, k) in self.kraus.iter().enumerate() {
+            let mut candidate = state.clone();
+            candidate.apply(k, qubits);
+
+            let p = norm_sqr(&candidate);
+            acc = acc + p;
+
+            if !p.is_zero() {
+                chosen = Some((idx, candidate, p));
+            }
+
+            if r < acc && chosen.is_some() {
+                break;
+            }
+        }
+
+        let (idx, candidate, p) = chosen.expect("the state has zero norm");
+        let scale = Complex::from((total / p).sqrt());
+        *state = StateVector::try_from(candidate.ket() * scale).unwrap();
+
+        idx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn assert_close(lhs: &DensityMatrix<f64>, rhs: &Operator<f64>) {
+        assert!(lhs
+            .operator()
+            .inner
+            .iter()
+            .zip(rhs.inner.iter())
+            .all(|(a, b)| (a - b).norm() < 1e-12));
+    }
+
+    #[test]
+    fn test_density_channels() {
+        let zero = Ket::from([1f64, 0.].as_slice());
+        let one = Ket::from([0f64, 1.].as_slice());
+        let plus = Ket::from([1f64, 1.].as_slice());
+
+        let mut rho = DensityMatrix::from_ket(&zero).unwrap();
+        Channel::depolarizing(1.)
+            .unwrap()
+            .apply_density(&mut rho, &[0]);
+        assert_close(&rho, &Operator::from([[0.5, 0.], [0., 0.5]]));
+
+        let mut rho = DensityMatrix::from_ket(&one).unwrap();
+        Channel::amplitude_damping(1.)
+            .unwrap()
+            .apply_density(&mut rho, &[0]);
+        assert_close(&rho, &Operator::from([[1., 0.], [0., 0.]]));
+
+        let mut rho = DensityMatrix::from_ket(&plus).unwrap();
+        Channel::phase_damping(1.)
+            .unwrap()
+            .apply_density(&mut rho, &[0]);
+        assert_close(&rho, &Operator::from([[0.5, 0.], [0., 0.5]]));
+
+        let mut rho = DensityMatrix::from_ket(&plus).unwrap();
+        Channel::phase_flip(0.5)
+            .unwrap()
+            .apply_density(&mut rho, &[0]);
+        assert_close(&rho, &Operator::from([[0.5, 0.], [0., 0.5]]));
+
+        let mut rho = DensityMatrix::from_ket(&(&zero & &zero)).unwrap();
+        Channel::bit_flip(0.25)
+            .unwrap()
+            .apply_density(&mut rho, &[1]);
+        assert_close(
+            &rho,
+            &Operator::from_diag([0.75, 0.25, 0., 0.].map(Complex::from)),
+        );
+
+        assert_eq!(
+            Channel::amplitude_damping(1.5),
+            Err(QomputeTypeError::NonTracePreserving)
+        );
+        assert_eq!(
+            Channel::bit_flip(1.5),
+            Err(QomputeTypeError::NonTracePreserving)
+        );
+        assert_eq!(
+            Channel::new(vec![Operator::from([[1f64, 0.], [0., 0.5]])]),
+            Err(QomputeTypeError::NonTracePreserving)
+        );
+    }
+
+    #[test]
+    fn test_trajectories() {
+        let channel = Channel::amplitude_damping(0.3).unwrap();
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let decayed = (0..2000)
+            .filter(|_| {
+                let mut state = StateVector::<f64>::new(2);
+                state.apply(&Operator::from([[0., 1.], [1., 0.]]), &[1]);
+                channel.apply_trajectory(&mut state, &[1], &mut rng) == 1
+            })
+            .count();
+
+        assert!((decayed as f64 / 2000. - 0.3).abs() < 0.05);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod channel;
+
+pub use channel::Channel;
@@ -0,0 +1,17 @@
+mod string;
+mod sum;
+
+pub use string::{Pauli, PauliString};
+pub use sum::PauliSum;
+
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq, Hash)]
+pub enum PauliError {
+    #[error("'{0}' is not one of I, X, Y or Z")]
+    InvalidChar(char),
+    #[error("Expected a Pauli string on {expected} qubits, but found {found}")]
+    NonMatchingQubits { expected: usize, found: usize },
+    #[error("A Pauli string on n qubits acts on a state of size {expected}, but found {found}")]
+    NonMatchingDimension { expected: usize, found: usize },
+}
@@ -0,0 +1,388 @@
+use crate::prelude::*;
+
+use std::fmt;
+use std::str::FromStr;
+
+use num::Zero;
+
+use super::PauliError;
+
+const WORD: usize = u64::BITS as usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pauli {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl Pauli {
+    fn from_bits(x: bool, z: bool) -> Self {
+        match (x, z) {
+            (false, false) => Pauli::I,
+            (true, false) => Pauli::X,
+            (true, true) => Pauli::Y,
+            (false, true) => Pauli::Z,
+        }
+    }
+
+    fn bits(self) -> (bool, bool) {
+        match self {
+            Pauli::I => (false, false),
+            Pauli::X => (true, false),
+            Pauli::Y => (true, true),
+            Pauli::Z => (false, true),
+        }
+    }
+
+    pub fn matrix<T: Float>(self) -> Operator<T> {
+        match self {
+            Pauli::I => gates::i(),
+            Pauli::X => gates::x(),
+            Pauli::Y => gates::y(),
+            Pauli::Z => gates::z(),
+        }
+    }
+}
+
+/// `i^k` for `k` taken modulo 4.
+pub(crate) fn phase_factor<T: Float>(k: u8) -> Complex<T> {
+    let (one, zero) = (T::one(), T::zero());
+    match k % 4 {
+        0 => Complex::new(one, zero),
+        1 => Complex::new(zero, one),
+        2 => Complex::new(-one, zero),
+        _ => Complex::new(zero, -one),
+    }
+}
+
+/// A tensor product of single-qubit Paulis times a phase `i^k`.
+///
+/// Each qubit is stored as an `x` and a `z` bit packed into words, with `Y`
+/// having both set, so products and commutation checks run a word at a time.
+/// As elsewhere, qubit `0` is the leftmost factor and the most significant
+/// bit of a basis state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PauliString {
+    n_qubits: usize,
+    phase: u8,
+    x: Vec<u64>,
+    z: Vec<u64>,
+}
+
+impl PauliString {
+    pub fn identity(n_qubits: usize) -> Self {
+        let words = (0..n_qubits).step_by(WORD).len();
+        Self {
+            n_qubits,
+            phase: 0,
+            x: vec![0; words],
+            z: vec![0; words],
+        }
+    }
+
+    /// A single Pauli on `qubit`, padded with identities.
+    pub fn single(n_qubits: usize, qubit: usize, pauli: Pauli) -> Self {
+        let mut string = Self::identity(n_qubits);
+        string.set(qubit, pauli);
+        string
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    /// The exponent `k` of the phase `i^k`, in `0..4`.
+    pub fn phase(&self) -> u8 {
+        self.phase
+    }
+
+    pub fn with_phase(mut self, phase: u8) -> Self {
+        self.phase = phase % 4;
+        self
+    }
+
+    pub fn get(&self, qubit: usize) -> Pauli {
+        assert!(qubit < self.n_qubits, "qubit {} is out of range", qubit);
+        let (word, bit) = (qubit / WORD, qubit % WORD);
+        Pauli::from_bits(self.x[word] >> bit & 1 == 1, self.z[word] >> bit & 1 == 1)
+    }
+
+    pub fn set(&mut self, qubit: usize, pauli: Pauli) {
+        assert!(qubit < self.n_qubits, "qubit {} is out of range", qubit);
+        let (word, mask) = (qubit / WORD, 1 << (qubit % WORD));
+        let (x, z) = pauli.bits();
+
+        self.x[word] = if x {
+            self.x[word] | mask
+        } else {
+            self.x[word] & !mask
+        };
+        self.z[word] = if z {
+            self.z[word] | mask
+        } else {
+            self.z[word] & !mask
+        };
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Pauli> + '_ {
+        (0..self.n_qubits).map(|q| self.get(q))
+    }
+
+    /// The number of non-identity factors.
+    pub fn weight(&self) -> usize {
+        self.x
+            .iter()
+            .zip(&self.z)
+            .map(|(x, z)| (x | z).count_ones() as usize)
+            .sum()
+    }
+
+    fn check_qubits(&self, other: &Self) -> Result<(), PauliError> {
+        if self.n_qubits == other.n_qubits {
+            Ok(())
+        } else {
+            Err(PauliError::NonMatchingQubits {
+                expected: self.n_qubits,
+                found: other.n_qubits,
+            })
+        }
+    }
+
+    /// Two Pauli strings either commute or anticommute, depending on the
+    /// parity of the number of qubits on which their factors differ and are
+    /// both non-identity.
+    pub fn commutes_with(&self, other: &Self) -> Result<bool, PauliError> {
+        self.check_qubits(other)?;
+
+        let anticommuting = (0..self.x.len())
+            .map(|w| ((self.x[w] & other.z[w]) ^ (self.z[w] & other.x[w])).count_ones())
+            .sum::<u32>();
+
+        Ok(anticommuting % 2 == 0)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, PauliError> {
+        self.check_qubits(rhs)?;
+
+        let mut phase = u32::from(self.phase) + u32::from(rhs.phase);
+        let mut out = Self::identity(self.n_qubits);
+
+        for w in 0..self.x.len() {
+            let (x1, z1, x2, z2) = (self.x[w], self.z[w], rhs.x[w], rhs.z[w]);
+            let (x1_only, y1, z1_only) = (x1 & !z1, x1 & z1, !x1 & z1);
+            let (x2_only, y2, z2_only) = (x2 & !z2, x2 & z2, !x2 & z2);
+
+            // XY = iZ, YZ = iX and ZX = iY, while the reversed products pick
+            // up -i.
+            let forward = (x1_only & y2) | (y1 & z2_only) | (z1_only & x2_only);
+            let backward = (y1 & x2_only) | (z1_only & y2) | (x1_only & z2_only);
+            phase += forward.count_ones() + 3 * backward.count_ones();
+
+            out.x[w] = x1 ^ x2;
+            out.z[w] = z1 ^ z2;
+        }
+
+        out.phase = (phase % 4) as u8;
+        Ok(out)
+    }
+
+    /// Bit masks over basis-state indices of the qubits with an `x` and a `z`
+    /// bit set, and the number of `Y` factors.
+    fn masks(&self) -> (usize, usize, u8) {
+        let (mut x_mask, mut z_mask, mut ys) = (0, 0, 0u8);
+
+        for (q, pauli) in self.iter().enumerate() {
+            let bit = 1 << (self.n_qubits - 1 - q);
+            let (x, z) = pauli.bits();
+            if x {
+                x_mask |= bit;
+            }
+            if z {
+                z_mask |= bit;
+            }
+            if x && z {
+                ys = (ys + 1) % 4;
+            }
+        }
+
+        (x_mask, z_mask, ys)
+    }
+
+    /// The non-zero entries `(row, col, value)` of the matrix, one per
+    /// column. Writing `Y = iXZ`, column `b` holds
+    /// `i^(k + #Y) (-1)^|b & z|` in row `b ^ x`.
+    pub(crate) fn entries<T: Float>(&self) -> impl Iterator<Item = (usize, usize, Complex<T>)> {
+        let (x_mask, z_mask, ys) = self.masks();
+        let base = phase_factor::<T>(self.phase + ys);
+
+        (0..1usize << self.n_qubits).map(move |b| {
+            let value = if (b & z_mask).count_ones() % 2 == 0 {
+                base
+            } else {
+                -base
+            };
+            (b ^ x_mask, b, value)
+        })
+    }
+
+    pub(crate) fn check_ket<T: Float>(&self, ket: &Ket<T>) -> Result<(), PauliError> {
+        let expected = 1usize
+            .checked_shl(self.n_qubits as u32)
+            .unwrap_or(usize::MAX);
+        if ket.inner.len() == expected {
+            Ok(())
+        } else {
+            Err(PauliError::NonMatchingDimension {
+                expected,
+                found: ket.inner.len(),
+            })
+        }
+    }
+
+    pub fn to_operator<T: Float>(&self) -> Operator<T> {
+        let dim = 1 << self.n_qubits;
+        let mut op = Operator::new_with_shape((dim, dim).into());
+        op.inner.iter_mut().for_each(|a| *a = Complex::zero());
+
+        for (row, col, value) in self.entries::<T>() {
+            op[(row, col)] = value;
+        }
+
+        op
+    }
+
+    pub fn to_sparse<T: Float>(&self) -> SparseOperator<T> {
+        let dim = 1 << self.n_qubits;
+        SparseOperator::from_triplets((dim, dim).into(), self.entries())
+            .expect("entries are in range")
+    }
+
+    pub fn apply<T: Float>(&self, ket: &Ket<T>) -> Result<Ket<T>, PauliError> {
+        self.check_ket(ket)?;
+
+        let mut out = ket.clone();
+        for (row, col, value) in self.entries::<T>() {
+            out.inner[row] = value * ket.inner[col];
+        }
+
+        Ok(out)
+    }
+
+    /// `<psi|P|psi>`, without building the matrix.
+    pub fn expectation<T: Float>(&self, ket: &Ket<T>) -> Result<Complex<T>, PauliError> {
+        self.check_ket(ket)?;
+
+        Ok(self
+            .entries::<T>()
+            .fold(Complex::zero(), |acc: Complex<T>, (row, col, value)| {
+                acc + ket.inner[row].conj() * value * ket.inner[col]
+            }))
+    }
+}
+
+impl FromIterator<Pauli> for PauliString {
+    fn from_iter<I: IntoIterator<Item = Pauli>>(iter: I) -> Self {
+        let paulis = iter.into_iter().collect::<Vec<_>>();
+        let mut string = Self::identity(paulis.len());
+        paulis
+            .into_iter()
+            .enumerate()
+            .for_each(|(q, p)| string.set(q, p));
+        string
+    }
+}
+
+/// Parses strings such as `XIZ`, `-Y` or `+iZZ`, with qubit `0` first.
+impl FromStr for PauliString {
+    type Err = PauliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (phase, rest) = match s {
+            _ if s.starts_with("-i") => (3, &s[2..]),
+            _ if s.starts_with("+i") => (1, &s[2..]),
+            _ if s.starts_with('i') => (1, &s[1..]),
+            _ if s.starts_with('-') => (2, &s[1..]),
+            _ if s.starts_with('+') => (0, &s[1..]),
+            _ => (0, s),
+        };
+
+        rest.chars()
+            .map(|c| match c {
+                'I' => Ok(Pauli::I),
+                'X' => Ok(Pauli::X),
+                'Y' => Ok(Pauli::Y),
+                'Z' => Ok(Pauli::Z),
+                c => Err(PauliError::InvalidChar(c)),
+            })
+            .collect::<Result<PauliString, _>>()
+            .map(|string| string.with_phase(phase))
+    }
+}
+
+impl fmt::Display for PauliString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(["", "i", "-", "-i"][self.phase as usize])?;
+        self.iter().try_for_each(|p| write!(f, "{:?}", p))
+    }
+}
+
+auto_ops::impl_op_ex!(*|lhs: &PauliString, rhs: &PauliString| -> PauliString {
+    lhs.checked_mul(rhs).unwrap_or_else(|err| panic!("{}", err))
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_pauli_string() {
+        let p = |s: &str| s.parse::<PauliString>().unwrap();
+
+        assert_eq!(p("X") * p("Y"), p("iZ"));
+        assert_eq!(p("Y") * p("X"), p("-iZ"));
+        assert_eq!(p("XZ") * p("ZX"), p("YY"));
+        assert_eq!((p("-iXYZI") * p("ZYXI")).to_string(), "-iYIYI");
+
+        assert!(p("XX").commutes_with(&p("ZZ")).unwrap());
+        assert!(!p("XI").commutes_with(&p("ZZ")).unwrap());
+        assert!(p("X").commutes_with(&p("XX")).is_err());
+        assert_eq!(p("IXIYZ").weight(), 3);
+        assert_eq!(
+            "XQ".parse::<PauliString>(),
+            Err(PauliError::InvalidChar('Q'))
+        );
+
+        let long = (0..100)
+            .map(|q| if q % 3 == 0 { Pauli::X } else { Pauli::Z })
+            .collect::<PauliString>();
+        assert_eq!((&long * &long).weight(), 0);
+        assert_eq!(long.get(99), Pauli::X);
+    }
+
+    #[test]
+    fn test_dense_and_expectation() {
+        let string = "iXYZ".parse::<PauliString>().unwrap();
+        let dense = (gates::x::<f64>() & gates::y()) & gates::z();
+        assert!(string
+            .to_operator::<f64>()
+            .approx_eq(&(dense.clone() * Complex::i())));
+
+        let ket = Ket::from(
+            (0..8)
+                .map(|i| Complex::new(i as f64 + 1., 0.5 - i as f64))
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+        let applied = &string.to_operator() * &ket;
+        assert!(string.apply(&ket).unwrap().approx_eq(&applied));
+        assert!(string
+            .expectation(&ket)
+            .unwrap()
+            .approx_eq(&(&ket.dagger() * &applied)));
+        assert!(string
+            .expectation(&Ket::from([1f64, 0.].as_slice()))
+            .is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::prelude::*;
+
+use std::collections::HashMap;
+
+use num::Zero;
+
+use super::string::phase_factor;
+use super::PauliError;
+
+/// A weighted sum of Pauli strings on a common set of qubits, as used to
+/// describe Hamiltonians. Phases are folded into the coefficients, so every
+/// stored string has phase zero.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauliSum<T: Float> {
+    n_qubits: usize,
+    terms: Vec<(Complex<T>, PauliString)>,
+}
+
+impl<T: Float> PauliSum<T> {
+    pub fn new(n_qubits: usize) -> Self {
+        Self {
+            n_qubits,
+            terms: Vec::new(),
+        }
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    pub fn terms(&self) -> &[(Complex<T>, PauliString)] {
+        &self.terms
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Appends `coeff * string`. Panics if the string acts on a different
+    /// number of qubits.
+    pub fn push(&mut self, coeff: Complex<T>, string: PauliString) {
+        assert_eq!(
+            string.n_qubits(),
+            self.n_qubits,
+            "expected a Pauli string on {} qubits",
+            self.n_qubits
+        );
+
+        let coeff = coeff * phase_factor(string.phase());
+        self.terms.push((coeff, string.with_phase(0)));
+    }
+
+    pub fn with_term(mut self, coeff: Complex<T>, string: PauliString) -> Self {
+        self.push(coeff, string);
+        self
+    }
+
+    fn check_qubits(&self, other: &Self) -> Result<(), PauliError> {
+        if self.n_qubits == other.n_qubits {
+            Ok(())
+        } else {
+            Err(PauliError::NonMatchingQubits {
+                expected: self.n_qubits,
+                found: other.n_qubits,
+            })
+        }
+    }
+
+    /// Merges repeated strings and drops terms whose coefficient is within
+    /// `tol` of zero, keeping the order of first appearance.
+    pub fn simplify(&mut self, tol: T) {
+        let mut index = HashMap::<PauliString, usize>::new();
+        let mut merged = Vec::<(Complex<T>, PauliString)>::new();
+
+        for (coeff, string) in self.terms.drain(..) {
+            match index.get(&string) {
+                Some(&i) => merged[i].0 = merged[i].0 + coeff,
+                None => {
+                    index.insert(string.clone(), merged.len());
+                    merged.push((coeff, string));
+                }
+            }
+        }
+
+        merged.retain(|(coeff, _)| coeff.norm() > tol);
+        self.terms = merged;
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, PauliError> {
+        self.check_qubits(rhs)?;
+
+        let mut out = self.clone();
+        out.terms.extend(rhs.terms.iter().cloned());
+        out.simplify(T::zero());
+        Ok(out)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, PauliError> {
+        self.check_qubits(rhs)?;
+
+        let mut out = Self::new(self.n_qubits);
+        for (a, p) in &self.terms {
+            for (b, q) in &rhs.terms {
+                out.push(*a * b, p.checked_mul(q)?);
+            }
+        }
+        out.simplify(T::zero());
+        Ok(out)
+    }
+
+    /// `[A, B]`. Only anticommuting pairs of strings contribute, each as
+    /// `2 a b PQ`.
+    pub fn commutator(&self, rhs: &Self) -> Result<Self, PauliError> {
+        self.check_qubits(rhs)?;
+
+        let two = Complex::from(T::from(2).unwrap());
+        let mut out = Self::new(self.n_qubits);
+        for (a, p) in &self.terms {
+            for (b, q) in &rhs.terms {
+                if !p.commutes_with(q)? {
+                    out.push(two * a * b, p.checked_mul(q)?);
+                }
+            }
+        }
+        out.simplify(T::zero());
+        Ok(out)
+    }
+
+    /// Whether every coefficient of `[A, B]` is within `tol` of zero.
+    pub fn commutes_with(&self, rhs: &Self, tol: T) -> Result<bool, PauliError> {
+        let mut commutator = self.commutator(rhs)?;
+        commutator.simplify(tol);
+        Ok(commutator.is_empty())
+    }
+
+    pub fn to_operator(&self) -> Operator<T> {
+        let dim = 1 << self.n_qubits;
+        let mut op = Operator::new_with_shape((dim, dim).into());
+        op.inner.iter_mut().for_each(|a| *a = Complex::zero());
+
+        for (coeff, string) in &self.terms {
+            for (row, col, value) in string.entries::<T>() {
+                op[(row, col)] = op[(row, col)] + coeff * value;
+            }
+        }
+
+        op
+    }
+
+    /// Collects every term into one sparse matrix, with at most
+    /// `len() * 2^n` entries.
+    pub fn to_sparse(&self) -> SparseOperator<T> {
+        let dim = 1 << self.n_qubits;
+        let triplets = self.terms.iter().flat_map(|(coeff, string)| {
+            string
+                .entries::<T>()
+                .map(move |(row, col, value)| (row, col, coeff * value))
+        });
+
+        SparseOperator::from_triplets((dim, dim).into(), triplets).expect("entries are in range")
+    }
+
+    /// `<psi|H|psi>`, summed term by term without building the matrix.
+    pub fn expectation(&self, ket: &Ket<T>) -> Result<Complex<T>, PauliError> {
+        self.terms
+            .iter()
+            .try_fold(Complex::zero(), |acc, (coeff, string)| {
+                Ok(acc + coeff * string.expectation(ket)?)
+            })
+    }
+}
+
+impl<T: Float> From<PauliString> for PauliSum<T> {
+    fn from(string: PauliString) -> Self {
+        Self::new(string.n_qubits()).with_term(Complex::from(T::one()), string)
+    }
+}
+
+auto_ops::impl_op_ex!(+ <T: Float> |lhs: &PauliSum<T>, rhs: &PauliSum<T>| -> PauliSum<T> {
+    lhs.checked_add(rhs).unwrap_or_else(|err| panic!("{}", err))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &PauliSum<T>, rhs: &PauliSum<T>| -> PauliSum<T> {
+    lhs.checked_mul(rhs).unwrap_or_else(|err| panic!("{}", err))
+});
+
+auto_ops::impl_op_ex!(* <T: Float> |lhs: &PauliSum<T>, rhs: Complex<T>| -> PauliSum<T> {
+    PauliSum {
+        n_qubits: lhs.n_qubits,
+        terms: lhs.terms.iter().map(|(c, s)| (c * rhs, s.clone())).collect(),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn p(s: &str) -> PauliString {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pauli_sum() {
+        // A transverse-field Ising chain on three qubits.
+        let ising = ["ZZI", "IZZ"].iter().fold(PauliSum::new(3), |h, s| {
+            h.with_term(Complex::from(-1.), p(s))
+        });
+        let field = ["XII", "IXI", "IIX"].iter().fold(PauliSum::new(3), |h, s| {
+            h.with_term(Complex::from(-0.5f64), p(s))
+        });
+        let h = &ising + &field;
+        assert_eq!(h.len(), 5);
+
+        let z = |q| PauliSum::from(PauliString::single(3, q, Pauli::Z));
+        assert!(ising.commutes_with(&z(0), 0.).unwrap());
+        assert!(!h.commutes_with(&z(0), 1e-12).unwrap());
+
+        let dense = h.to_operator();
+        assert!(dense.hermitian());
+        assert!(dense.approx_eq(&(ising.to_operator() + field.to_operator())));
+
+        let (values, vectors) = dense.eigh().unwrap();
+        let ground = h.expectation(&vectors[0]).unwrap();
+        assert!(ground.approx_eq(&Complex::from(values[0])));
+
+        // The product of two sums squares out, and Pauli strings square to one.
+        let mut squared = &field * &field;
+        squared.simplify(1e-12);
+        assert!(squared
+            .to_operator()
+            .approx_eq(&(&field.to_operator() * &field.to_operator())));
+        assert_eq!(squared.terms()[0], (Complex::from(0.75), p("III")));
+
+        let mut zero = &h + &(&h * Complex::from(-1.));
+        zero.simplify(0.);
+        assert!(zero.is_empty());
+    }
+}
@@ -1,7 +1,21 @@
 pub use crate::{
+    circuit::{Circuit, CircuitError, Condition, GateKind, Instruction, QasmError, QasmVersion},
     cmpx,
     complex::{
+        approx::ApproxEq,
         braket::{Bra, ComplexObject, Ket, Operator, Shape},
-        gates, Complex, Float, If32, If64, ToBra, ToKet, ToOperator,
+        diagonal::DiagonalOperator,
+        gates,
+        permutation::PermutationOperator,
+        sparse::SparseOperator,
+        view::{KetView, KetViewMut, OperatorView, OperatorViewMut},
+        Complex, Float, If32, If64, ToBra, ToKet, ToOperator,
+    },
+    linalg,
+    noise::Channel,
+    pauli::{Pauli, PauliError, PauliString, PauliSum},
+    state::{
+        expectation, partial_trace, variance, Control, DensityMatrix, Estimate, Expectation,
+        StateVector,
     },
 };
@@ -0,0 +1,290 @@
+use crate::prelude::*;
+
+use num::Zero;
+
+use crate::complex::braket::QomputeTypeError;
+
+/// A mixed state `rho`, stored as a dense operator.
+///
+/// Construction checks that `rho` is Hermitian, positive semidefinite and
+/// has unit trace. The last two checks allow for a tolerance of
+/// `sqrt(T::epsilon())` relative to the dimension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityMatrix<T: Float> {
+    pub(crate) rho: Operator<T>,
+}
+
+impl<T: Float> DensityMatrix<T> {
+    /// The pure state `|k><k|`, normalizing `k` first. Fails for an empty
+    /// ket, or one whose norm is zero or not finite.
+    pub fn from_ket(ket: &Ket<T>) -> Result<Self, QomputeTypeError> {
+        if ket.size() == 0 {
+            return Err(QomputeTypeError::EmptyInput);
+        }
+
+        let rho = ket * &ket.dagger();
+        let norm = rho.trace().re;
+
+        if !norm.is_finite() {
+            return Err(QomputeTypeError::NonFinite);
+        }
+        if norm <= T::zero() {
+            return Err(QomputeTypeError::ZeroNorm);
+        }
+
+        Ok(Self {
+            rho: rho * Complex::from(norm.recip()),
+        })
+    }
+
+    /// The mixture `sum_i p_i |k_i><k_i|` of normalized kets.
+    pub fn from_ensemble<'a, I>(ensemble: I) -> Result<Self, QomputeTypeError>
+    where
+        T: 'a,
+        I: IntoIterator<Item = (T, &'a Ket<T>)>,
+    {
+        let mut ensemble = ensemble.into_iter();
+        let (p, ket) = ensemble.next().ok_or(QomputeTypeError::EmptyInput)?;
+
+        if p < T::zero() {
+            return Err(QomputeTypeError::NonPositive);
+        }
+        let mut rho = ket * &ket.dagger() * Complex::from(p);
+
+        for (p, ket) in ensemble {
+            if ket.size() != rho.rows() {
+                return Err(QomputeTypeError::NonMatchingSizes);
+            }
+            if p < T::zero() {
+                return Err(QomputeTypeError::NonPositive);
+            }
+
+            rho += ket * &ket.dagger() * Complex::from(p);
+        }
+
+        Self::try_from(rho)
+    }
+
+    pub fn operator(&self) -> &Operator<T> {
+        &self.rho
+    }
+
+    pub fn into_operator(self) -> Operator<T> {
+        self.rho
+    }
+
+    pub fn dim(&self) -> usize {
+        self.rho.rows()
+    }
+
+    /// Replaces `rho` by `U rho U^\dagger`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `unitary` is not of the same shape as `rho`.
+    pub fn evolve(&mut self, unitary: &Operator<T>) {
+        assert_eq!(unitary.shape(), self.rho.shape());
+        self.rho = unitary * &self.rho * unitary.dagger();
+    }
+
+    /// Replaces `rho` by `G rho G^\dagger`, where `G` acts on the given
+    /// qubits only. Follows the qubit ordering of [`StateVector::apply`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rho` is not a state of qubits or if `gate` and `qubits` are
+    /// not compatible as in [`StateVector::apply`].
+    pub fn apply(&mut self, gate: &Operator<T>, qubits: &[usize]) {
+        self.rho = conjugate(&self.rho, gate, qubits);
+    }
+
+    pub fn trace(&self) -> T {
+        self.rho.trace().re
+    }
+
+    /// `Tr(rho^2)`, which is `1` exactly for pure states.
+    pub fn purity(&self) -> T {
+        // Tr(rho^2) = sum_ij |rho_ij|^2 for Hermitian rho.
+        self.rho
+            .inner
+            .iter()
+            .fold(T::zero(), |acc, a| acc + a.norm_sqr())
+    }
+
+    /// The probability of each computational basis state, i.e. the diagonal.
+    pub fn probabilities(&self) -> Vec<T> {
+        (0..self.dim()).map(|i| self.rho[(i, i)].re).collect()
+    }
+
+    /// The probability `<k|rho|k>` of projecting onto the normalized `ket`.
+    pub fn probability(&self, ket: &Ket<T>) -> T {
+        assert_eq!(ket.size(), self.dim());
+
+        (0..self.dim())
+            .flat_map(|i| (0..self.dim()).map(move |j| (i, j)))
+            .map(|(i, j)| ket[i].conj() * self.rho[(i, j)] * ket[j])
+            .fold(Complex::<T>::zero(), |acc, a| acc + a)
+            .re
+    }
+}
+
+/// Computes `G op G^\dagger` as `(G (G op)^\dagger)^\dagger`, applying `G`
+/// to one column at a time.
+pub(crate) fn conjugate<T: Float>(
+    op: &Operator<T>,
+    gate: &Operator<T>,
+    qubits: &[usize],
+) -> Operator<T> {
+    let left = apply_columns(op, gate, qubits);
+    apply_columns(&left.dagger(), gate, qubits).dagger()
+}
+
+fn apply_columns<T: Float>(op: &Operator<T>, gate: &Operator<T>, qubits: &[usize]) -> Operator<T> {
+    let mut out = op.clone();
+
+    for col in 0..op.cols() {
+        let ket = (0..op.rows()).map(|row| op[(row, col)]).to_ket();
+        let mut state = StateVector::try_from(ket).expect("not a state of qubits");
+        state.apply(gate, qubits);
+
+        for (row, &a) in state.amplitudes().iter().enumerate() {
+            out[(row, col)] = a;
+        }
+    }
+
+    out
+}
+
+pub(crate) fn tolerance<T: Float>(dim: usize) -> T {
+    T::epsilon().sqrt() * T::from(dim.max(1)).unwrap()
+}
+
+/// Checks positive semidefiniteness of a Hermitian matrix by attempting an
+/// `LDL^\dagger` factorization, letting pivots within `tol` of zero through as
+/// long as the rest of their column vanishes too.
+fn positive_semidefinite<T: Float>(op: &Operator<T>, tol: T) -> bool {
+    let n = op.rows();
+    let mut a = op.clone();
+
+    for k in 0..n {
+        let pivot = a[(k, k)].re;
+
+        if pivot < -tol {
+            return false;
+        }
+
+        if pivot <= tol {
+            if (k + 1..n).any(|i| a[(i, k)].norm() > tol) {
+                return false;
+            }
+            continue;
+        }
+
+        for i in k + 1..n {
+            let l = a[(i, k)] / pivot;
+            for j in k + 1..n {
+                let update = l * a[(k, j)];
+                a[(i, j)] = a[(i, j)] - update;
+            }
+        }
+    }
+
+    true
+}
+
+impl<T: Float> TryFrom<Operator<T>> for DensityMatrix<T> {
+    type Error = QomputeTypeError;
+
+    fn try_from(rho: Operator<T>) -> Result<Self, Self::Error> {
+        if rho.size() == 0 {
+            return Err(QomputeTypeError::EmptyInput);
+        }
+
+        if !rho.hermitian() {
+            return Err(QomputeTypeError::NonHermitian);
+        }
+
+        let tol = tolerance(rho.rows());
+
+        if (rho.trace() - Complex::from(T::one())).norm() > tol {
+            return Err(QomputeTypeError::NonUnitTrace);
+        }
+
+        if !positive_semidefinite(&rho, tol) {
+            return Err(QomputeTypeError::NonPositive);
+        }
+
+        Ok(Self { rho })
+    }
+}
+
+impl<T: Float> TryFrom<&Ket<T>> for DensityMatrix<T> {
+    type Error = QomputeTypeError;
+
+    fn try_from(value: &Ket<T>) -> Result<Self, Self::Error> {
+        Self::from_ket(value)
+    }
+}
+
+impl<T: Float> From<DensityMatrix<T>> for Operator<T> {
+    fn from(value: DensityMatrix<T>) -> Self {
+        value.rho
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_pure_and_mixed() {
+        let zero = Ket::from([1f64, 0.].as_slice());
+        let one = Ket::from([0f64, 1.].as_slice());
+        let plus = Ket::from([1f64, 1.].as_slice());
+
+        let rho = DensityMatrix::from_ket(&plus).unwrap();
+        assert!((rho.trace() - 1.).abs() < 1e-12);
+        assert!((rho.purity() - 1.).abs() < 1e-12);
+        assert_eq!(rho.probabilities(), [0.5, 0.5]);
+
+        let mixed = DensityMatrix::from_ensemble([(0.5, &zero), (0.5, &one)]).unwrap();
+        assert!((mixed.purity() - 0.5).abs() < 1e-12);
+        assert!((mixed.probability(&(&plus * cmpx!(0.5f64).sqrt())) - 0.5).abs() < 1e-12);
+
+        let mut flipped = DensityMatrix::from_ket(&zero).unwrap();
+        flipped.evolve(&Operator::from([[0., 1.], [1., 0.]]));
+        assert_eq!(flipped.probabilities(), [0., 1.]);
+
+        let mut pair = DensityMatrix::from_ket(&(&zero & &plus)).unwrap();
+        pair.apply(&Operator::from([[0., 1.], [1., 0.]]), &[0]);
+        assert_eq!(pair, DensityMatrix::from_ket(&(&one & &plus)).unwrap());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            DensityMatrix::try_from(Operator::from([[0.5f64, 0.5], [0., 0.5]])),
+            Err(QomputeTypeError::NonHermitian)
+        );
+        assert_eq!(
+            DensityMatrix::try_from(Operator::from([[1f64, 0.], [0., 1.]])),
+            Err(QomputeTypeError::NonUnitTrace)
+        );
+        assert_eq!(
+            DensityMatrix::try_from(Operator::from([[0.5f64, 1.], [1., 0.5]])),
+            Err(QomputeTypeError::NonPositive)
+        );
+        assert!(DensityMatrix::try_from(Operator::from([[0.5f64, 0.5], [0.5, 0.5]])).is_ok());
+        assert!(DensityMatrix::try_from(Operator::from([[1f64, 0.], [0., 0.]])).is_ok());
+
+        assert_eq!(
+            DensityMatrix::from_ket(&Ket::from([0f64, 0.].as_slice())),
+            Err(QomputeTypeError::ZeroNorm)
+        );
+        assert_eq!(
+            DensityMatrix::from_ket(&Ket::from([f64::NAN, 1.].as_slice())),
+            Err(QomputeTypeError::NonFinite)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::prelude::*;
+
+use num::Zero;
+use rand::Rng;
+
+use super::measure::Cdf;
+use crate::complex::braket::QomputeTypeError;
+use crate::complex::ops::check_shape;
+
+/// The sample mean of a shot-based estimate and its standard error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate<T: Float> {
+    pub mean: T,
+    pub std_error: T,
+}
+
+/// Expectation values of observables in a pure or mixed state.
+pub trait Expectation<T: Float> {
+    /// `<O>`, which is real for Hermitian `O`.
+    fn expectation(&self, op: &Operator<T>) -> Result<Complex<T>, QomputeTypeError>;
+
+    /// `<O^dagger O> - |<O>|^2`, which for Hermitian `O` is the spread of
+    /// measurement outcomes.
+    fn variance(&self, op: &Operator<T>) -> Result<T, QomputeTypeError>;
+
+    /// The probability of each outcome of measuring in an orthonormal basis.
+    fn basis_probabilities(&self, basis: &[Ket<T>]) -> Vec<T>;
+
+    fn expectations(&self, ops: &[Operator<T>]) -> Result<Vec<Complex<T>>, QomputeTypeError> {
+        ops.iter().map(|op| self.expectation(op)).collect()
+    }
+
+    /// Estimates `<O>` of a Hermitian observable from `shots` simulated
+    /// measurements in its eigenbasis, as an experiment would.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shots` is less than two, since the standard error is then
+    /// undefined.
+    fn estimate<R: Rng + ?Sized>(
+        &self,
+        op: &Operator<T>,
+        shots: usize,
+        rng: &mut R,
+    ) -> Result<Estimate<T>, QomputeTypeError> {
+        assert!(shots >= 2, "at least two shots are needed");
+
+        self.expectation(op)?;
+        let (values, vectors) = op.eigh()?;
+        let cdf = Cdf::new(&self.basis_probabilities(&vectors));
+
+        let outcomes = (0..shots)
+            .map(|_| values[cdf.sample(rng)])
+            .collect::<Vec<_>>();
+
+        let n = T::from(shots).unwrap();
+        let mean = outcomes.iter().fold(T::zero(), |acc, &x| acc + x) / n;
+        let spread = outcomes
+            .iter()
+            .fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean))
+            / (n - T::one());
+
+        Ok(Estimate {
+            mean,
+            std_error: (spread / n).sqrt(),
+        })
+    }
+}
+
+fn norm_sqr<T: Float>(ket: &Ket<T>) -> T {
+    ket.inner
+        .iter()
+        .fold(T::zero(), |acc, a| acc + a.norm_sqr())
+}
+
+/// The squared norm to divide by, rejecting kets that cannot be normalized.
+fn checked_norm_sqr<T: Float>(ket: &Ket<T>) -> Result<T, QomputeTypeError> {
+    let norm = norm_sqr(ket);
+
+    if !norm.is_finite() {
+        Err(QomputeTypeError::NonFinite)
+    } else if norm <= T::zero() {
+        Err(QomputeTypeError::ZeroNorm)
+    } else {
+        Ok(norm)
+    }
+}
+
+/// The ket does not need to be normalized, but fails with
+/// [`QomputeTypeError::ZeroNorm`] or [`QomputeTypeError::NonFinite`] if it
+/// cannot be.
+impl<T: Float> Expectation<T> for Ket<T> {
+    fn expectation(&self, op: &Operator<T>) -> Result<Complex<T>, QomputeTypeError> {
+        let n = self.size();
+        check_shape("take the expectation value", (n, n).into(), op.shape())?;
+
+        let norm = checked_norm_sqr(self)?;
+        let applied = op * self;
+        Ok((&self.dagger() * &applied) / norm)
+    }
+
+    fn variance(&self, op: &Operator<T>) -> Result<T, QomputeTypeError> {
+        let n = self.size();
+        check_shape("take the variance", (n, n).into(), op.shape())?;
+
+        let norm = checked_norm_sqr(self)?;
+        let applied = op * self;
+
+        let mean = (&self.dagger() * &applied) / norm;
+        Ok((norm_sqr(&applied) / norm - mean.norm_sqr()).max(T::zero()))
+    }
+
+    fn basis_probabilities(&self, basis: &[Ket<T>]) -> Vec<T> {
+        let norm = norm_sqr(self);
+        basis
+            .iter()
+            .map(|v| (&v.dagger() * self).norm_sqr() / norm)
+            .collect()
+    }
+}
+
+impl<T: Float> Expectation<T> for StateVector<T> {
+    fn expectation(&self, op: &Operator<T>) -> Result<Complex<T>, QomputeTypeError> {
+        self.ket().expectation(op)
+    }
+
+    fn variance(&self, op: &Operator<T>) -> Result<T, QomputeTypeError> {
+        self.ket().variance(op)
+    }
+
+    fn basis_probabilities(&self, basis: &[Ket<T>]) -> Vec<T> {
+        self.ket().basis_probabilities(basis)
+    }
+}
+
+/// `Tr(rho A)`, summed without forming the product.
+fn trace_product<T: Float>(rho: &Operator<T>, op: &Operator<T>) -> Complex<T> {
+    let n = rho.rows();
+    (0..n)
+        .flat_map(|i| (0..n).map(move |j| (i, j)))
+        .fold(Complex::zero(), |acc, (i, j)| {
+            acc + rho[(i, j)] * op[(j, i)]
+        })
+}
+
+impl<T: Float> Expectation<T> for DensityMatrix<T> {
+    fn expectation(&self, op: &Operator<T>) -> Result<Complex<T>, QomputeTypeError> {
+        check_shape("take the expectation value", self.rho.shape(), op.shape())?;
+        Ok(trace_product(&self.rho, op))
+    }
+
+    fn variance(&self, op: &Operator<T>) -> Result<T, QomputeTypeError> {
+        check_shape("take the variance", self.rho.shape(), op.shape())?;
+
+        let mean = trace_product(&self.rho, op);
+        let second = trace_product(&self.rho, &(&op.dagger() * op)).re;
+        Ok((second - mean.norm_sqr()).max(T::zero()))
+    }
+
+    fn basis_probabilities(&self, basis: &[Ket<T>]) -> Vec<T> {
+        basis.iter().map(|v| self.probability(v)).collect()
+    }
+}
+
+/// `<psi|O|psi>`, or `Tr(rho O)` for a mixed state.
+pub fn expectation<T: Float, S: Expectation<T>>(
+    state: &S,
+    op: &Operator<T>,
+) -> Result<Complex<T>, QomputeTypeError> {
+    state.expectation(op)
+}
+
+pub fn variance<T: Float, S: Expectation<T>>(
+    state: &S,
+    op: &Operator<T>,
+) -> Result<T, QomputeTypeError> {
+    state.variance(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_expectation() {
+        let amp = 0.5f64.sqrt();
+        let plus = Ket::from([amp, amp].as_slice());
+        let x = gates::x::<f64>();
+        let z = gates::z::<f64>();
+
+        assert!(expectation(&plus, &x)
+            .unwrap()
+            .approx_eq(&Complex::from(1.)));
+        assert!(variance(&plus, &x).unwrap() < 1e-12);
+        assert!((variance(&plus, &z).unwrap() - 1.).abs() < 1e-12);
+
+        let all = plus
+            .expectations(&[x.clone(), gates::y(), z.clone()])
+            .unwrap();
+        assert!(all
+            .iter()
+            .zip([1., 0., 0.])
+            .all(|(a, b)| a.approx_eq(&Complex::from(b))));
+        assert!(plus.expectation(&gates::cnot()).is_err());
+        assert!(Ket::from([0f64, 0.].as_slice()).expectation(&x).is_err());
+        assert!(Ket::from([0f64, 0.].as_slice()).variance(&x).is_err());
+
+        // A maximally mixed qubit has no preferred direction.
+        let mixed =
+            DensityMatrix::from_ensemble([(0.5, &plus), (0.5, &Ket::from([amp, -amp].as_slice()))])
+                .unwrap();
+        assert!(mixed.expectation(&x).unwrap().norm() < 1e-12);
+        assert!((mixed.variance(&x).unwrap() - 1.).abs() < 1e-12);
+
+        let pure = DensityMatrix::from_ket(&plus).unwrap();
+        let h = (&x & &z) + (&z & &x);
+        let ket = plus.tensorprod(&Ket::from([0.6, 0.8].as_slice()));
+        let rho = DensityMatrix::from_ket(&ket).unwrap();
+        assert!(ket
+            .expectation(&h)
+            .unwrap()
+            .approx_eq(&rho.expectation(&h).unwrap()));
+        assert!((ket.variance(&h).unwrap() - rho.variance(&h).unwrap()).abs() < 1e-12);
+        assert!(pure.variance(&x).unwrap() < 1e-12);
+    }
+
+    #[test]
+    fn test_estimate() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let state = Ket::from([0.6f64, 0.8].as_slice());
+        let exact = state.expectation(&gates::z()).unwrap().re;
+        let estimate = state.estimate(&gates::z(), 10_000, &mut rng).unwrap();
+
+        let expected_error = ((1. - exact * exact) / 10_000.).sqrt();
+        assert!((estimate.std_error - expected_error).abs() < 1e-3);
+        assert!((estimate.mean - exact).abs() < 4. * expected_error);
+
+        // An eigenstate always gives the same outcome.
+        let rho = DensityMatrix::from_ket(&Ket::from([1f64, 1.].as_slice())).unwrap();
+        let estimate = rho.estimate(&gates::x(), 100, &mut rng).unwrap();
+        assert!((estimate.mean - 1.).abs() < 1e-12 && estimate.std_error < 1e-12);
+
+        assert!(state
+            .estimate(&Operator::from([[0f64, 1.], [0., 0.]]), 10, &mut rng)
+            .is_err());
+
+        // An observable that is Hermitian only up to rounding.
+        let u = &gates::cnot::<f64>() * &(&gates::rx(0.7) & &gates::ry(-1.3));
+        let d = Operator::from_diag([-2., -0.5, 1., 3.].into_iter().map(Complex::from));
+        let h = &(&u * &d) * &u.dagger();
+        let state = &u * &Ket::from([0f64, 0., 1., 0.].as_slice());
+        let estimate = state.estimate(&h, 100, &mut rng).unwrap();
+        assert!((estimate.mean - 1.).abs() < 1e-9 && estimate.std_error < 1e-9);
+    }
+}
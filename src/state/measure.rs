@@ -0,0 +1,190 @@
+use crate::prelude::*;
+
+use std::collections::BTreeMap;
+
+use num::Zero;
+use rand::Rng;
+
+impl<T: Float> Ket<T> {
+    /// The Born-rule probability of each basis state. The ket does not need
+    /// to be normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the norm of the ket is zero or not finite.
+    pub fn probabilities(&self) -> Vec<T> {
+        let probs = self.inner.iter().map(|a| a.norm_sqr()).collect::<Vec<_>>();
+        let total = probs.iter().fold(T::zero(), |acc, &p| acc + p);
+        assert!(
+            total.is_finite() && total > T::zero(),
+            "cannot measure a ket with zero or non-finite norm"
+        );
+
+        probs.into_iter().map(|p| p / total).collect()
+    }
+
+    fn n_qubits(&self) -> usize {
+        assert!(self.size().is_power_of_two(), "not a register of qubits");
+        self.size().trailing_zeros() as usize
+    }
+
+    /// Measures `qubits` in the computational basis, collapsing the state.
+    /// The outcome for `qubits[i]` is at index `i` of the returned vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ket is not a register of qubits, if a qubit is out of
+    /// range, or if the norm of the ket is zero or not finite.
+    pub fn measure<R: Rng + ?Sized>(&mut self, qubits: &[usize], rng: &mut R) -> Vec<bool> {
+        let n = self.n_qubits();
+        assert!(qubits.iter().all(|&q| q < n), "qubit out of range");
+
+        let key = |idx: usize| {
+            qubits
+                .iter()
+                .fold(0, |acc, &q| (acc << 1) | ((idx >> (n - 1 - q)) & 1))
+        };
+
+        let probs = self.probabilities();
+        let outcome = key(Cdf::new(&probs).sample(rng));
+
+        let p = (0..self.size())
+            .filter(|&i| key(i) == outcome)
+            .fold(T::zero(), |acc, i| acc + probs[i]);
+        let norm = (p * self
+            .inner
+            .iter()
+            .fold(T::zero(), |acc, a| acc + a.norm_sqr()))
+        .sqrt();
+
+        for i in 0..self.size() {
+            self[i] = if key(i) == outcome {
+                self[i] / norm
+            } else {
+                Complex::<T>::zero()
+            };
+        }
+
+        (0..qubits.len())
+            .rev()
+            .map(|j| (outcome >> j) & 1 == 1)
+            .collect()
+    }
+
+    /// Draws `shots` basis states without collapsing the ket, returning how
+    /// often each bitstring was seen. Qubit `0` is the leftmost character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ket is not a register of qubits, or if its norm is zero
+    /// or not finite.
+    pub fn sample<R: Rng + ?Sized>(&self, shots: usize, rng: &mut R) -> BTreeMap<String, usize> {
+        let n = self.n_qubits();
+        let cdf = Cdf::new(&self.probabilities());
+
+        let mut counts = BTreeMap::new();
+        for _ in 0..shots {
+            let idx = cdf.sample(rng);
+            *counts.entry(format!("{idx:0n$b}")).or_insert(0) += 1;
+        }
+
+        counts
+    }
+}
+
+impl<T: Float> StateVector<T> {
+    pub fn measure<R: Rng + ?Sized>(&mut self, qubits: &[usize], rng: &mut R) -> Vec<bool> {
+        self.ket_mut().measure(qubits, rng)
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, shots: usize, rng: &mut R) -> BTreeMap<String, usize> {
+        self.ket().sample(shots, rng)
+    }
+}
+
+/// The running totals of a probability vector, built once so that each draw
+/// is a binary search rather than a scan.
+pub(crate) struct Cdf {
+    cumulative: Vec<f64>,
+}
+
+impl Cdf {
+    pub(crate) fn new<T: Float>(probs: &[T]) -> Self {
+        let mut acc = 0.;
+        let cumulative = probs
+            .iter()
+            .map(|p| {
+                acc += p.to_f64().unwrap();
+                acc
+            })
+            .collect();
+
+        Self { cumulative }
+    }
+
+    /// Draws an index with probability proportional to its weight. Indices
+    /// of zero weight are never drawn unless every weight is zero.
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let total = self.cumulative.last().copied().unwrap_or(0.);
+        let r = rng.gen::<f64>() * total;
+
+        // Rounding can push `r` up to `total`, so stop at the last index of
+        // non-zero weight.
+        let last = self.cumulative.partition_point(|&c| c < total);
+        self.cumulative.partition_point(|&c| c <= r).min(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_measure_bell() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut circ = Circuit::new(3, 0);
+        circ.h(0).cnot(0, 2);
+
+        for _ in 0..20 {
+            let mut state = StateVector::<f64>::new(3);
+            circ.run(&mut state).unwrap();
+
+            let first = state.measure(&[0], &mut rng);
+            let second = state.measure(&[2, 1], &mut rng);
+            assert_eq!(first[0], second[0]);
+            assert!(!second[1]);
+
+            let probs = state.ket().probabilities();
+            assert_eq!(probs.iter().filter(|&&p| (p - 1.).abs() < 1e-12).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let ket = Ket::from([0.6f64, 0., 0., 0.8].as_slice());
+
+        let counts = ket.sample(10_000, &mut StdRng::seed_from_u64(7));
+        assert_eq!(counts.keys().collect::<Vec<_>>(), ["00", "11"]);
+        assert_eq!(counts.values().sum::<usize>(), 10_000);
+        assert!((counts["11"] as f64 / 10_000. - 0.64).abs() < 0.02);
+
+        let again = ket.sample(10_000, &mut StdRng::seed_from_u64(7));
+        assert_eq!(counts, again);
+
+        let cdf = super::Cdf::new(&[0f64, 0.25, 0., 0.75, 0.]);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!((0..1000)
+            .map(|_| cdf.sample(&mut rng))
+            .all(|i| i == 1 || i == 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "zero or non-finite norm")]
+    fn test_measure_zero_ket() {
+        let mut ket = Ket::from([0f64, 0.].as_slice());
+        ket.measure(&[0], &mut StdRng::seed_from_u64(0));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod density;
+pub mod expectation;
+mod measure;
+pub mod register;
+pub mod trace;
+
+pub use density::DensityMatrix;
+pub use expectation::{expectation, variance, Estimate, Expectation};
+pub use register::{Control, StateVector};
+pub use trace::partial_trace;
@@ -0,0 +1,173 @@
+use crate::prelude::*;
+
+use num::Zero;
+
+use crate::complex::braket::QomputeTypeError;
+
+/// The offset into the full space of every basis state of `subsystems`, in
+/// row-major order with `subsystems[0]` most significant.
+fn offsets(dims: &[usize], subsystems: &[usize]) -> Vec<usize> {
+    let strides = dims
+        .iter()
+        .rev()
+        .scan(1, |stride, &d| {
+            let s = *stride;
+            *stride *= d;
+            Some(s)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect::<Vec<_>>();
+
+    subsystems.iter().fold(vec![0], |acc, &s| {
+        let stride = strides[s];
+        acc.iter()
+            .flat_map(|&o| (0..dims[s]).map(move |d| o + d * stride))
+            .collect()
+    })
+}
+
+/// Splits the subsystems into the kept ones, in the order given, and the
+/// traced-out ones, after checking `keep` and `dims` against `size`.
+fn split(
+    size: usize,
+    keep: &[usize],
+    dims: &[usize],
+) -> Result<(Vec<usize>, Vec<usize>), QomputeTypeError> {
+    if dims.is_empty() {
+        return Err(QomputeTypeError::EmptyInput);
+    }
+
+    if dims.iter().product::<usize>() != size {
+        return Err(QomputeTypeError::NonMatchingSizes);
+    }
+
+    let mut seen = vec![false; dims.len()];
+    for &k in keep {
+        if k >= dims.len() || seen[k] {
+            return Err(QomputeTypeError::NonMatchingSizes);
+        }
+        seen[k] = true;
+    }
+
+    let traced = (0..dims.len()).filter(|&s| !seen[s]).collect::<Vec<_>>();
+
+    Ok((offsets(dims, keep), offsets(dims, &traced)))
+}
+
+/// Traces out every subsystem not in `keep` from an operator on the space
+/// `dims[0] x dims[1] x ...` (the order used by `tensorprod`). The kept
+/// subsystems appear in the result in the order they are listed in `keep`.
+pub fn partial_trace<T: Float>(
+    op: &Operator<T>,
+    keep: &[usize],
+    dims: &[usize],
+) -> Result<Operator<T>, QomputeTypeError> {
+    if op.rows() != op.cols() {
+        return Err(QomputeTypeError::NonMatchingSizes);
+    }
+
+    let (kept, traced) = split(op.rows(), keep, dims)?;
+    let mut out = Operator::new_with_shape((kept.len(), kept.len()).into());
+
+    for (a, &ka) in kept.iter().enumerate() {
+        for (b, &kb) in kept.iter().enumerate() {
+            out[(a, b)] = traced
+                .iter()
+                .map(|&t| op[(ka + t, kb + t)])
+                .fold(Complex::<T>::zero(), |acc, x| acc + x);
+        }
+    }
+
+    Ok(out)
+}
+
+impl<T: Float> Ket<T> {
+    /// The reduced density matrix of the subsystems in `keep`, computed
+    /// directly from the amplitudes without forming `|k><k|`.
+    ///
+    /// See [`partial_trace`] for the meaning of `keep` and `dims`.
+    pub fn partial_trace(
+        &self,
+        keep: &[usize],
+        dims: &[usize],
+    ) -> Result<Operator<T>, QomputeTypeError> {
+        let (kept, traced) = split(self.size(), keep, dims)?;
+        let mut out = Operator::new_with_shape((kept.len(), kept.len()).into());
+
+        for (a, &ka) in kept.iter().enumerate() {
+            for (b, &kb) in kept.iter().enumerate() {
+                out[(a, b)] = traced
+                    .iter()
+                    .map(|&t| self[ka + t] * self[kb + t].conj())
+                    .fold(Complex::<T>::zero(), |acc, x| acc + x);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl<T: Float> DensityMatrix<T> {
+    pub fn partial_trace(&self, keep: &[usize], dims: &[usize]) -> Result<Self, QomputeTypeError> {
+        partial_trace(self.operator(), keep, dims).map(|rho| Self { rho })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::braket::QomputeTypeError;
+    use crate::prelude::*;
+
+    use super::partial_trace;
+
+    #[test]
+    fn test_product_state() {
+        let a = Ket::from([cmpx!(0.6), cmpx!(0.8 j)]);
+        let b = Ket::from([0f64, 1., 0.].as_slice());
+        let c = Ket::from([0.5f64.sqrt(), -(0.5f64.sqrt())].as_slice());
+
+        let abc = &(&a & &b) & &c;
+        let rho = &abc * &abc.dagger();
+        let dims = [2, 3, 2];
+
+        let rho_a = &a * &a.dagger();
+        let rho_ac = &(&a & &c) * &(&a & &c).dagger();
+        let rho_ca = &(&c & &a) * &(&c & &a).dagger();
+
+        for (keep, expected) in [(&[0][..], &rho_a), (&[0, 2], &rho_ac), (&[2, 0], &rho_ca)] {
+            let from_op = partial_trace(&rho, keep, &dims).unwrap();
+            let from_ket = abc.partial_trace(keep, &dims).unwrap();
+
+            assert_eq!(from_op.shape(), expected.shape());
+            assert!(from_op
+                .inner
+                .iter()
+                .zip(from_ket.inner.iter())
+                .zip(expected.inner.iter())
+                .all(|((x, y), z)| (x - z).norm() < 1e-12 && (y - z).norm() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_bell_state() {
+        let amp = 0.5f64.sqrt();
+        let bell = Ket::from([amp, 0., 0., amp].as_slice());
+
+        let rho = DensityMatrix::from_ket(&bell)
+            .unwrap()
+            .partial_trace(&[1], &[2, 2])
+            .unwrap();
+        assert!((rho.purity() - 0.5).abs() < 1e-12);
+
+        assert_eq!(
+            bell.partial_trace(&[0], &[2, 3]),
+            Err(QomputeTypeError::NonMatchingSizes)
+        );
+        assert_eq!(
+            bell.partial_trace(&[1, 1], &[2, 2]),
+            Err(QomputeTypeError::NonMatchingSizes)
+        );
+    }
+}